@@ -0,0 +1,465 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{self, AtomicBool},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+#[cfg(all(feature = "gamepad", target_os = "linux"))]
+use livesplit_core::hotkey::KeyCode;
+use livesplit_core::{
+    hotkey::{Hook, Hotkey},
+    HotkeyConfig, HotkeySystem, SharedTimer,
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(all(feature = "gamepad", target_os = "linux"))]
+use crate::gamepad;
+use crate::safeguards::{self, Reset};
+
+/// All the actions that can be bound to hotkeys, in the order they are shown
+/// in the settings.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Split,
+    Reset,
+    Undo,
+    Skip,
+    Pause,
+    UndoAllPauses,
+    PreviousComparison,
+    NextComparison,
+    ToggleTimingMethod,
+    PreviousNotes,
+    NextNotes,
+    ToggleGlobalHotkeys,
+}
+
+impl Action {
+    pub const ALL: [Action; 12] = [
+        Action::Split,
+        Action::Reset,
+        Action::Undo,
+        Action::Skip,
+        Action::Pause,
+        Action::UndoAllPauses,
+        Action::PreviousComparison,
+        Action::NextComparison,
+        Action::ToggleTimingMethod,
+        Action::PreviousNotes,
+        Action::NextNotes,
+        Action::ToggleGlobalHotkeys,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Split => "Start / Split",
+            Action::Reset => "Reset",
+            Action::Undo => "Undo Split",
+            Action::Skip => "Skip Split",
+            Action::Pause => "Pause",
+            Action::UndoAllPauses => "Undo All Pauses",
+            Action::PreviousComparison => "Previous Comparison",
+            Action::NextComparison => "Next Comparison",
+            Action::ToggleTimingMethod => "Toggle Timing Method",
+            Action::PreviousNotes => "Notes of Previous Segment",
+            Action::NextNotes => "Notes of Next Segment",
+            Action::ToggleGlobalHotkeys => "Toggle Global Hotkeys",
+        }
+    }
+
+    /// Returns the hotkey of the action in the hotkey config. The notes and
+    /// toggling the global hotkeys aren't part of it.
+    pub fn hotkey(self, config: &HotkeyConfig) -> Option<Hotkey> {
+        match self {
+            Action::Split => config.split,
+            Action::Reset => config.reset,
+            Action::Undo => config.undo,
+            Action::Skip => config.skip,
+            Action::Pause => config.pause,
+            Action::UndoAllPauses => config.undo_all_pauses,
+            Action::PreviousComparison => config.previous_comparison,
+            Action::NextComparison => config.next_comparison,
+            Action::ToggleTimingMethod => config.toggle_timing_method,
+            Action::PreviousNotes | Action::NextNotes | Action::ToggleGlobalHotkeys => None,
+        }
+    }
+
+    pub fn set_hotkey(self, config: &mut HotkeyConfig, hotkey: Option<Hotkey>) {
+        match self {
+            Action::Split => config.split = hotkey,
+            Action::Reset => config.reset = hotkey,
+            Action::Undo => config.undo = hotkey,
+            Action::Skip => config.skip = hotkey,
+            Action::Pause => config.pause = hotkey,
+            Action::UndoAllPauses => config.undo_all_pauses = hotkey,
+            Action::PreviousComparison => config.previous_comparison = hotkey,
+            Action::NextComparison => config.next_comparison = hotkey,
+            Action::ToggleTimingMethod => config.toggle_timing_method = hotkey,
+            Action::PreviousNotes | Action::NextNotes | Action::ToggleGlobalHotkeys => {}
+        }
+    }
+
+    /// Whether the hotkey of the action is stored in the hotkey config of the
+    /// timer.
+    pub fn is_in_hotkey_config(self) -> bool {
+        !matches!(
+            self,
+            Action::PreviousNotes | Action::NextNotes | Action::ToggleGlobalHotkeys
+        )
+    }
+
+    /// Whether livesplit-core's hotkey system can apply the action on its own,
+    /// as it needs neither the safeguards nor the timer window.
+    fn is_handled_by_system(self) -> bool {
+        matches!(
+            self,
+            Action::Undo | Action::Skip | Action::Pause | Action::UndoAllPauses
+        )
+    }
+
+    /// Whether the action is applied by the timer window rather than directly
+    /// on the timer.
+    fn is_handled_by_app(self) -> bool {
+        matches!(
+            self,
+            Action::PreviousComparison
+                | Action::NextComparison
+                | Action::ToggleTimingMethod
+                | Action::PreviousNotes
+                | Action::NextNotes
+                | Action::ToggleGlobalHotkeys
+        )
+    }
+}
+
+/// Whether the hotkeys are registered globally, only react while the timer
+/// window is focused, or both.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HotkeyMode {
+    #[default]
+    Global,
+    FocusedOnly,
+    Both,
+}
+
+impl HotkeyMode {
+    fn is_global(self) -> bool {
+        self != HotkeyMode::FocusedOnly
+    }
+
+    fn is_focused(self) -> bool {
+        self != HotkeyMode::Global
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Source {
+    Global,
+    Window,
+}
+
+/// With both modes, a key press in the focused window is seen by the global
+/// hook and the window. The second one within this duration is dropped.
+const SAME_PRESS: Duration = Duration::from_millis(100);
+
+static TRIGGERED: Mutex<Vec<Action>> = Mutex::new(Vec::new());
+/// Whether a reset was ignored because of the two presses safeguard, so the
+/// timer window can tell the user to press it again.
+static RESET_IGNORED: AtomicBool = AtomicBool::new(false);
+/// The hotkeys that couldn't be registered globally, until the timer window
+/// tells the user about them.
+static FAILED: Mutex<Vec<Hotkey>> = Mutex::new(Vec::new());
+
+#[derive(Clone)]
+struct Trigger {
+    timer: SharedTimer,
+    active: Arc<AtomicBool>,
+    recent: Arc<Mutex<BTreeMap<Action, (Source, Instant)>>>,
+}
+
+impl Trigger {
+    fn trigger(&self, action: Action, source: Source) {
+        {
+            let mut recent = self.recent.lock().unwrap();
+            if let Some(&(other, at)) = recent.get(&action) {
+                if other != source && at.elapsed() < SAME_PRESS {
+                    return;
+                }
+            }
+            recent.insert(action, (source, Instant::now()));
+        }
+
+        if action.is_handled_by_app() {
+            TRIGGERED.lock().unwrap().push(action);
+        } else if self.active.load(atomic::Ordering::Relaxed) {
+            apply(&self.timer, action);
+        }
+    }
+}
+
+pub struct AppHotkeys {
+    hook: Option<Hook>,
+    trigger: Trigger,
+    registered: Vec<Hotkey>,
+    focused_bindings: Vec<(Hotkey, Action)>,
+    #[cfg(all(feature = "gamepad", target_os = "linux"))]
+    gamepad_bindings: Arc<Mutex<Vec<(KeyCode, Action)>>>,
+    #[cfg(all(feature = "gamepad", target_os = "linux"))]
+    _gamepads: gamepad::Gamepads,
+    pub global_hotkeys_enabled: bool,
+}
+
+impl AppHotkeys {
+    pub fn new(timer: SharedTimer) -> Self {
+        let hook = match Hook::new() {
+            Ok(hook) => Some(hook),
+            Err(e) => {
+                log::error!("Failed to create the hook for the application hotkeys: {e}");
+                None
+            }
+        };
+        let trigger = Trigger {
+            timer,
+            active: Arc::new(AtomicBool::new(true)),
+            recent: Default::default(),
+        };
+
+        #[cfg(all(feature = "gamepad", target_os = "linux"))]
+        let gamepad_bindings = Arc::new(Mutex::new(Vec::<(KeyCode, Action)>::new()));
+        #[cfg(all(feature = "gamepad", target_os = "linux"))]
+        let gamepads = {
+            let bindings = gamepad_bindings.clone();
+            let trigger = trigger.clone();
+            gamepad::Gamepads::spawn(move |button| {
+                let actions: Vec<Action> = bindings
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .filter(|&&(bound, _)| bound == button)
+                    .map(|&(_, action)| action)
+                    .collect();
+                for action in actions {
+                    trigger.trigger(action, Source::Global);
+                }
+            })
+        };
+
+        Self {
+            hook,
+            trigger,
+            registered: Vec::new(),
+            focused_bindings: Vec::new(),
+            #[cfg(all(feature = "gamepad", target_os = "linux"))]
+            gamepad_bindings,
+            #[cfg(all(feature = "gamepad", target_os = "linux"))]
+            _gamepads: gamepads,
+            global_hotkeys_enabled: true,
+        }
+    }
+
+    /// Registers the hotkeys of the config, the ones of the actions that the
+    /// config doesn't cover and all the alternate hotkeys, depending on the mode either
+    /// globally or for the focused timer window. The hotkeys that the hotkey
+    /// system took over are skipped. Gamepad buttons are always bound, as they
+    /// aren't tied to a window. Returns the hotkeys that couldn't be
+    /// registered globally.
+    pub fn configure(
+        &mut self,
+        config: &HotkeyConfig,
+        app_primaries: &BTreeMap<Action, Hotkey>,
+        alternates: &BTreeMap<Action, Hotkey>,
+        mode: HotkeyMode,
+        handled_by_system: bool,
+    ) -> Vec<Hotkey> {
+        if let Some(hook) = &self.hook {
+            for hotkey in self.registered.drain(..) {
+                let _ = hook.unregister(hotkey);
+            }
+        }
+
+        let primary = Action::ALL.into_iter().filter_map(|action| {
+            if handled_by_system && action.is_handled_by_system() {
+                return None;
+            }
+            let hotkey = if action.is_in_hotkey_config() {
+                action.hotkey(config)
+            } else {
+                app_primaries.get(&action).copied()
+            };
+            Some((hotkey?, action))
+        });
+        let alternate = alternates.iter().map(|(&action, &hotkey)| (hotkey, action));
+
+        #[cfg(all(feature = "gamepad", target_os = "linux"))]
+        let mut gamepad_bindings = Vec::new();
+        let mut failed = Vec::new();
+        self.focused_bindings.clear();
+
+        for (hotkey, action) in primary.chain(alternate) {
+            #[cfg(all(feature = "gamepad", target_os = "linux"))]
+            if gamepad::is_gamepad_button(hotkey.key_code) {
+                gamepad_bindings.push((hotkey.key_code, action));
+                continue;
+            }
+
+            if mode.is_focused() {
+                self.focused_bindings.push((hotkey, action));
+            }
+            if !mode.is_global() {
+                continue;
+            }
+
+            let hook = match &self.hook {
+                Some(hook) => hook,
+                None => {
+                    failed.push(hotkey);
+                    continue;
+                }
+            };
+            let trigger = self.trigger.clone();
+            match hook.register(hotkey, move || trigger.trigger(action, Source::Global)) {
+                Ok(()) => self.registered.push(hotkey),
+                Err(e) => {
+                    log::error!("Failed to register the hotkey {hotkey}: {e}");
+                    failed.push(hotkey);
+                }
+            }
+        }
+
+        #[cfg(all(feature = "gamepad", target_os = "linux"))]
+        {
+            *self.gamepad_bindings.lock().unwrap() = gamepad_bindings;
+        }
+
+        failed
+    }
+
+    /// Triggers the actions bound to a key that was pressed while the timer
+    /// window is focused. Returns whether any action is bound to it.
+    pub fn handle_key(&self, hotkey: Hotkey) -> bool {
+        let mut handled = false;
+        for &(bound, action) in &self.focused_bindings {
+            if bound == hotkey {
+                self.trigger.trigger(action, Source::Window);
+                handled = true;
+            }
+        }
+        handled
+    }
+
+    /// Activates or deactivates the hotkeys that control the timer, along
+    /// with the hotkey system.
+    pub fn set_active(&self, active: bool) {
+        self.trigger.active.store(active, atomic::Ordering::Relaxed);
+    }
+}
+
+fn apply(timer: &SharedTimer, action: Action) {
+    let mut timer = timer.write().unwrap();
+    match action {
+        Action::Split => safeguards::split_or_start(&mut timer),
+        Action::Reset => match safeguards::check_reset(&timer) {
+            Reset::Allowed => timer.reset(true),
+            // The timer window asks for the confirmation.
+            Reset::NeedsConfirmation => TRIGGERED.lock().unwrap().push(action),
+            Reset::Ignored => RESET_IGNORED.store(true, atomic::Ordering::Relaxed),
+        },
+        Action::Undo => timer.undo_split(),
+        Action::Skip => timer.skip_split(),
+        Action::Pause => timer.toggle_pause_or_start(),
+        Action::UndoAllPauses => timer.undo_all_pauses(),
+        _ => {}
+    }
+}
+
+/// Passes the hotkeys of the actions that need neither the safeguards nor the
+/// timer window on to livesplit-core's hotkey system. Splitting and resetting
+/// always go through the safeguards, so the application registers those
+/// itself. When the hotkeys are also used while the timer window is focused,
+/// the application handles all of them, so a key press that both the window
+/// and the global hook see isn't applied twice. The same happens if the
+/// hotkey system fails to register them. Returns whether the hotkey system
+/// took them over.
+pub fn configure_system(
+    hotkey_system: &mut HotkeySystem,
+    config: HotkeyConfig,
+    mode: HotkeyMode,
+) -> bool {
+    let mut system_config = config;
+    let mut stripped = config;
+    for action in Action::ALL {
+        if !action.is_handled_by_system() {
+            action.set_hotkey(&mut system_config, None);
+        }
+        action.set_hotkey(&mut stripped, None);
+    }
+
+    if mode == HotkeyMode::Global {
+        match hotkey_system.set_config(system_config) {
+            Ok(()) => return true,
+            Err(e) => log::error!("Failed to register the hotkeys with the hotkey system: {e}"),
+        }
+    }
+    let _ = hotkey_system.set_config(stripped);
+    false
+}
+
+/// Remembers the hotkeys that couldn't be registered globally, so the timer
+/// window can tell the user about them without blocking.
+pub fn warn_about_failures(failed: &[Hotkey]) {
+    if failed.is_empty() {
+        return;
+    }
+    log::warn!(
+        "{} hotkeys couldn't be registered globally. They can still be used while the timer window is focused by changing the hotkey mode in the settings.",
+        failed.len()
+    );
+    FAILED.lock().unwrap().extend_from_slice(failed);
+}
+
+/// Takes the hotkeys that couldn't be registered globally since the last call.
+pub fn take_failures() -> Vec<Hotkey> {
+    std::mem::take(&mut *FAILED.lock().unwrap())
+}
+
+/// Takes all the actions that were triggered since the last call.
+pub fn take_triggered() -> Vec<Action> {
+    std::mem::take(&mut *TRIGGERED.lock().unwrap())
+}
+
+/// Whether a reset was ignored since the last call.
+pub fn take_reset_ignored() -> bool {
+    RESET_IGNORED.swap(false, atomic::Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use livesplit_core::hotkey::{KeyCode, Modifiers};
+
+    use super::*;
+
+    #[test]
+    fn system_only_handles_plain_timer_actions() {
+        for action in Action::ALL {
+            if action.is_handled_by_system() {
+                assert!(action.is_in_hotkey_config());
+                assert!(!action.is_handled_by_app());
+                assert!(!matches!(action, Action::Split | Action::Reset));
+            }
+        }
+    }
+
+    #[test]
+    fn failures_are_taken_once() {
+        let hotkey = KeyCode::F13.with_modifiers(Modifiers::empty());
+        warn_about_failures(&[]);
+        warn_about_failures(&[hotkey]);
+        assert_eq!(take_failures(), [hotkey]);
+        assert!(take_failures().is_empty());
+    }
+}
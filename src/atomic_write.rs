@@ -0,0 +1,155 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Writes the file by first writing to a temporary file next to it, syncing it
+/// to disk and then renaming it over the original. A crash or a full disk
+/// while writing therefore never leaves a truncated file behind. The previous
+/// version of the file is kept as a `.bak` file.
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    write_with(path, |file| file.write_all(contents.as_ref()))
+}
+
+/// Like [`write`], but the contents are written by the closure.
+pub fn write_with(path: &Path, write: impl FnOnce(&mut File) -> io::Result<()>) -> io::Result<()> {
+    let temp_path = sibling(path, ".tmp")?;
+
+    if let Err(e) = write_synced(&temp_path, write) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    if path.exists() {
+        // Failing to back up the previous version shouldn't keep the new
+        // version from being saved.
+        if let Err(e) = fs::copy(path, sibling(path, ".bak")?) {
+            log::warn!("Failed to back up {}: {e}", path.display());
+        }
+    }
+
+    if let Err(e) = fs::rename(&temp_path, path) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    // Make sure the rename itself is persisted as well.
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().and_then(|dir| File::open(dir).ok()) {
+        let _ = dir.sync_all();
+    }
+
+    Ok(())
+}
+
+fn write_synced(path: &Path, write: impl FnOnce(&mut File) -> io::Result<()>) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    write(&mut file)?;
+    file.sync_all()
+}
+
+fn sibling(path: &Path, suffix: &str) -> io::Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "The path is not a file."))?;
+    let mut sibling = OsString::from(file_name);
+    sibling.push(suffix);
+    Ok(path.with_file_name(sibling))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for each test, so they can run in parallel.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "livesplit-one-atomic-write-{}-{name}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn files_in(dir: &Path) -> Vec<OsString> {
+        let mut files = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn keeps_previous_contents_as_backup() {
+        let dir = test_dir("backup");
+        let path = dir.join("Splits.lss");
+
+        write(&path, "first").unwrap();
+        assert!(!dir.join("Splits.lss.bak").exists());
+
+        write(&path, "second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(
+            fs::read_to_string(dir.join("Splits.lss.bak")).unwrap(),
+            "first"
+        );
+        assert_eq!(files_in(&dir), ["Splits.lss", "Splits.lss.bak"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn fails_without_file_name() {
+        let dir = test_dir("no-file-name");
+
+        let error = write(&dir.join(".."), "contents").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(files_in(&dir).is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_write_keeps_original_and_backup() {
+        let dir = test_dir("failed-write");
+        let path = dir.join("Splits.lss");
+        write(&path, "first").unwrap();
+        write(&path, "second").unwrap();
+
+        let error = write_with(&path, |file| {
+            file.write_all(b"partial")?;
+            Err(io::Error::other("disk full"))
+        })
+        .unwrap_err();
+        assert_eq!(error.to_string(), "disk full");
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(
+            fs::read_to_string(dir.join("Splits.lss.bak")).unwrap(),
+            "first"
+        );
+        assert_eq!(files_in(&dir), ["Splits.lss", "Splits.lss.bak"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_rename_leaves_no_temporary_file() {
+        let dir = test_dir("failed-rename");
+        // A file can't replace a directory that isn't empty, not even as root.
+        let path = dir.join("Splits.lss");
+        fs::create_dir(&path).unwrap();
+        fs::write(path.join("inside"), "original").unwrap();
+
+        assert!(write(&path, "new").is_err());
+
+        assert_eq!(fs::read_to_string(path.join("inside")).unwrap(), "original");
+        assert_eq!(files_in(&dir), ["Splits.lss"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,308 @@
+use std::{collections::VecDeque, rc::Rc, sync::Mutex, time::Duration};
+
+use druid::{
+    lens::Identity,
+    theme,
+    widget::{CrossAxisAlignment, Flex, Label, LineBreaking, List, ListIter, Scroll},
+    Color, Data, LensExt, Widget, WidgetExt,
+};
+use livesplit_core::{
+    timing::formatter::{Complete, TimeFormatter},
+    SharedTimer,
+};
+
+use crate::{
+    consts::{BUTTON_BORDER, COLUMN_LABEL_FONT, GRID_BORDER, MARGIN, SPACING},
+    refresh::Refresh,
+    striped_row::{Striped, StripedRow},
+};
+
+/// The log target the auto splitting runtime uses for everything the script
+/// prints, as well as for its own diagnostics.
+pub const LOG_TARGET: &str = "Auto Splitter";
+
+const MAX_LOG_LINES: usize = 500;
+const REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+
+struct CapturedLog {
+    lines: VecDeque<LogLine>,
+    generation: u64,
+}
+
+static CAPTURED_LOG: Mutex<CapturedLog> = Mutex::new(CapturedLog {
+    lines: VecDeque::new(),
+    generation: 0,
+});
+
+/// Receives all the log records of the auto splitting runtime. This is hooked
+/// up to the logger regardless of whether logging to a file is enabled, so the
+/// inspector always has the messages available. That's also where attaching
+/// to and detaching from a process shows up, as the runtime doesn't expose
+/// the process it is attached to.
+pub fn capture_log(record: &log::Record) {
+    let mut captured = CAPTURED_LOG.lock().unwrap();
+    if captured.lines.len() == MAX_LOG_LINES {
+        captured.lines.pop_front();
+    }
+    captured.lines.push_back(LogLine {
+        time: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+        level: record.level(),
+        message: record.args().to_string(),
+    });
+    captured.generation += 1;
+}
+
+#[derive(Clone, PartialEq)]
+struct LogLine {
+    time: String,
+    level: log::Level,
+    message: String,
+}
+
+#[derive(Clone, PartialEq)]
+struct Status {
+    game_time: String,
+    loading_times: String,
+    is_game_time_initialized: bool,
+    is_game_time_paused: bool,
+    variables: Vec<(String, String)>,
+}
+
+#[derive(Clone, Data)]
+pub struct State {
+    #[data(ignore)]
+    timer: SharedTimer,
+    status: Rc<Status>,
+    log: Rc<Vec<LogLine>>,
+    #[data(ignore)]
+    log_generation: u64,
+}
+
+impl State {
+    pub fn new(timer: SharedTimer) -> Self {
+        let mut state = Self {
+            status: Rc::new(query_status(&timer)),
+            timer,
+            log: Rc::new(Vec::new()),
+            log_generation: u64::MAX,
+        };
+        state.refresh();
+        state
+    }
+
+    fn refresh(&mut self) {
+        let status = query_status(&self.timer);
+        if status != *self.status {
+            self.status = Rc::new(status);
+        }
+
+        let captured = CAPTURED_LOG.lock().unwrap();
+        if captured.generation != self.log_generation {
+            self.log_generation = captured.generation;
+            self.log = Rc::new(captured.lines.iter().cloned().collect());
+        }
+    }
+}
+
+fn query_status(timer: &SharedTimer) -> Status {
+    let timer = timer.read().unwrap();
+    let formatter = Complete::new();
+    Status {
+        game_time: formatter.format(timer.current_time().game_time).to_string(),
+        loading_times: formatter.format(timer.loading_times()).to_string(),
+        is_game_time_initialized: timer.is_game_time_initialized(),
+        is_game_time_paused: timer.is_game_time_paused(),
+        variables: timer
+            .run()
+            .metadata()
+            .custom_variables()
+            .map(|(name, variable)| (name.to_owned(), variable.value.clone()))
+            .collect(),
+    }
+}
+
+#[derive(Clone, Data)]
+struct Row {
+    index: usize,
+    key: String,
+    value: String,
+    #[data(same_fn = "PartialEq::eq")]
+    level: Option<log::Level>,
+}
+
+#[derive(Clone, Data)]
+struct Variables(Rc<Status>);
+
+#[derive(Clone, Data)]
+struct LogLines(Rc<Vec<LogLine>>);
+
+impl ListIter<Row> for Variables {
+    fn for_each(&self, mut cb: impl FnMut(&Row, usize)) {
+        for (index, (key, value)) in self.0.variables.iter().enumerate() {
+            let row = Row {
+                index,
+                key: key.clone(),
+                value: value.clone(),
+                level: None,
+            };
+            cb(&row, index);
+        }
+    }
+
+    fn for_each_mut(&mut self, mut cb: impl FnMut(&mut Row, usize)) {
+        self.for_each(|row, index| cb(&mut row.clone(), index))
+    }
+
+    fn data_len(&self) -> usize {
+        self.0.variables.len()
+    }
+}
+
+impl ListIter<Row> for LogLines {
+    fn for_each(&self, mut cb: impl FnMut(&Row, usize)) {
+        for (index, line) in self.0.iter().enumerate() {
+            let row = Row {
+                index,
+                key: line.time.clone(),
+                value: line.message.clone(),
+                level: Some(line.level),
+            };
+            cb(&row, index);
+        }
+    }
+
+    fn for_each_mut(&mut self, mut cb: impl FnMut(&mut Row, usize)) {
+        self.for_each(|row, index| cb(&mut row.clone(), index))
+    }
+
+    fn data_len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+fn level_color(level: Option<log::Level>) -> Option<Color> {
+    match level? {
+        log::Level::Error => Some(Color::rgb8(0xff, 0x55, 0x55)),
+        log::Level::Warn => Some(Color::rgb8(0xff, 0xc8, 0x3d)),
+        _ => None,
+    }
+}
+
+impl Striped for Row {
+    fn index(&self) -> usize {
+        self.index
+    }
+}
+
+fn row(key_width: f64) -> impl Widget<Row> {
+    StripedRow::new(
+        Flex::row()
+            .cross_axis_alignment(CrossAxisAlignment::Start)
+            .with_spacer(6.0)
+            .with_child(Label::new(|row: &Row, _: &_| row.key.clone()).fix_width(key_width))
+            .with_spacer(GRID_BORDER)
+            .with_flex_child(
+                Label::new(|row: &Row, _: &_| row.value.clone())
+                    .with_line_break_mode(LineBreaking::WordWrap)
+                    .env_scope(|env, row: &Row| {
+                        if let Some(color) = level_color(row.level) {
+                            env.set(theme::TEXT_COLOR, color);
+                        }
+                    })
+                    .expand_width(),
+                1.0,
+            )
+            .with_spacer(6.0)
+            .padding(2.0),
+    )
+}
+
+fn status_entry(
+    name: &'static str,
+    value: impl Fn(&State) -> String + 'static,
+) -> impl Widget<State> {
+    Flex::row()
+        .with_child(Label::new(name).fix_width(160.0))
+        .with_flex_child(
+            Label::new(move |state: &State, _: &_| value(state)).expand_width(),
+            1.0,
+        )
+}
+
+fn status() -> impl Widget<State> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(status_entry("Game Time", |state| {
+            if state.status.is_game_time_initialized {
+                state.status.game_time.clone()
+            } else {
+                String::from("Not initialized")
+            }
+        }))
+        .with_child(status_entry("Loading", |state| {
+            if state.status.is_game_time_paused {
+                String::from("Yes")
+            } else {
+                String::from("No")
+            }
+        }))
+        .with_child(status_entry("Loading Times", |state| {
+            state.status.loading_times.clone()
+        }))
+}
+
+fn section(name: &'static str) -> impl Widget<State> {
+    Label::new(name).with_font(COLUMN_LABEL_FONT)
+}
+
+pub fn root_widget() -> impl Widget<State> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(status())
+        .with_spacer(SPACING)
+        .with_child(section("Variables"))
+        .with_spacer(GRID_BORDER)
+        .with_child(
+            List::new(|| row(160.0))
+                .border(BUTTON_BORDER, 1.0)
+                .lens(Identity.map(|state: &State| Variables(state.status.clone()), |_, _| {})),
+        )
+        .with_spacer(SPACING)
+        .with_child(section("Log"))
+        .with_spacer(GRID_BORDER)
+        .with_flex_child(
+            Scroll::new(
+                List::new(|| row(90.0))
+                    .border(BUTTON_BORDER, 1.0)
+                    .lens(Identity.map(|state: &State| LogLines(state.log.clone()), |_, _| {})),
+            )
+            .vertical()
+            .expand_height(),
+            1.0,
+        )
+        .padding(MARGIN)
+        .controller(Refresh::new(REFRESH_INTERVAL, State::refresh))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_latest_lines() {
+        for index in 0..MAX_LOG_LINES + 10 {
+            capture_log(
+                &log::Record::builder()
+                    .target(LOG_TARGET)
+                    .level(log::Level::Info)
+                    .args(format_args!("Line {index}"))
+                    .build(),
+            );
+        }
+
+        let captured = CAPTURED_LOG.lock().unwrap();
+        assert_eq!(captured.lines.len(), MAX_LOG_LINES);
+        assert_eq!(captured.lines[0].message, "Line 10");
+        assert_eq!(captured.generation, (MAX_LOG_LINES + 10) as u64);
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::BTreeMap;
+
+use livesplit_core::{Run, Time, TimeSpan, TimingMethod};
+
+use crate::timing::{other_timing_method, TIMING_METHODS};
+
+/// A change to the segment times of a run. All the other times of the run,
+/// like the split times of the comparisons and the final times of the
+/// attempts, follow from the changed segment times.
+#[derive(Copy, Clone)]
+pub enum Operation {
+    /// Adds the offset to the segment times, like for a consistent error in
+    /// the load removal.
+    Shift(TimeSpan),
+    /// Multiplies the segment times by the factor, like for converting
+    /// between PAL and NTSC.
+    Scale(f64),
+    /// Copies the segment times from the other timing method.
+    Copy,
+}
+
+impl Operation {
+    fn apply(self, segment: Time, target: TimingMethod) -> Option<TimeSpan> {
+        match self {
+            Operation::Shift(offset) => Some(segment[target]? + offset),
+            Operation::Scale(factor) => Some(TimeSpan::from_seconds(
+                segment[target]?.total_seconds() * factor,
+            )),
+            Operation::Copy => segment[other_timing_method(target)],
+        }
+    }
+}
+
+/// Parses a factor like `1.2` or a ratio like `60/50`.
+pub fn parse_factor(text: &str) -> Option<f64> {
+    let factor = match text.split_once('/') {
+        Some((numerator, denominator)) => {
+            numerator.trim().parse::<f64>().ok()? / denominator.trim().parse::<f64>().ok()?
+        }
+        None => text.trim().parse().ok()?,
+    };
+    (factor.is_finite() && factor > 0.0).then_some(factor)
+}
+
+/// Turns the split times into segment times. A skipped split has no segment
+/// time, the segment after it covers both.
+fn segment_times(split_times: &[Time]) -> Vec<Time> {
+    let mut previous = [TimeSpan::zero(); 2];
+    split_times
+        .iter()
+        .map(|split_time| {
+            let mut segment_time = Time::new();
+            for (timing_method, previous) in TIMING_METHODS.into_iter().zip(&mut previous) {
+                if let Some(time) = split_time[timing_method] {
+                    segment_time[timing_method] = Some(time - *previous);
+                    *previous = time;
+                }
+            }
+            segment_time
+        })
+        .collect()
+}
+
+/// Sums up the segment times of the attempt, which is its final time if it
+/// reached the last split. Skipped splits have no segment time, as the segment
+/// after them covers them.
+fn final_time(run: &Run, id: i32, timing_method: TimingMethod) -> Option<TimeSpan> {
+    let mut total = TimeSpan::zero();
+    for segment in run.segments() {
+        if let Some(time) = segment.segment_history().get(id)?[timing_method] {
+            total += time;
+        }
+    }
+    run.segments().last()?.segment_history().get(id)?[timing_method]?;
+    Some(total)
+}
+
+/// Applies the operation to the times of the target timing method of the
+/// selected segments.
+pub fn apply(run: &mut Run, selected: &[bool], operation: Operation, target: TimingMethod) {
+    let is_selected = |index: usize| selected.get(index).copied().unwrap_or_default();
+
+    for comparison in run.custom_comparisons().to_vec() {
+        let split_times: Vec<Time> = run
+            .segments()
+            .iter()
+            .map(|segment| segment.comparison(&comparison))
+            .collect();
+        let segment_times = segment_times(&split_times);
+
+        let mut total = TimeSpan::zero();
+        for (index, segment) in run.segments_mut().iter_mut().enumerate() {
+            let segment_time = if is_selected(index) {
+                operation.apply(segment_times[index], target)
+            } else {
+                segment_times[index][target]
+            };
+            let split_time = &mut segment.comparison_mut(&comparison)[target];
+            *split_time = segment_time.map(|segment_time| {
+                total += segment_time;
+                total
+            });
+        }
+    }
+
+    // How much the final time of each attempt changes.
+    let mut changes = BTreeMap::<i32, TimeSpan>::new();
+    for (index, segment) in run.segments_mut().iter_mut().enumerate() {
+        if !is_selected(index) {
+            continue;
+        }
+
+        let best = segment.best_segment_time();
+        segment.best_segment_time_mut()[target] = operation.apply(best, target);
+
+        for (id, time) in segment.segment_history_mut().iter_mut() {
+            let old = time[target];
+            let new = operation.apply(*time, target);
+            time[target] = new;
+            if let (Some(old), Some(new)) = (old, new) {
+                *changes.entry(*id).or_default() += new - old;
+            }
+        }
+    }
+
+    // The attempts can't be changed in place, so the history gets rebuilt.
+    let copy_whole_run = matches!(operation, Operation::Copy) && selected.iter().all(|&s| s);
+    let attempts: Vec<_> = run
+        .attempt_history()
+        .iter()
+        .map(|attempt| {
+            let mut time = attempt.time();
+            let finished = time.real_time.is_some() || time.game_time.is_some();
+            time[target] = match operation {
+                Operation::Copy if copy_whole_run => time[other_timing_method(target)],
+                // Copying may fill in segment times that were missing, so the
+                // change can't be tracked. The final time is whatever the
+                // segments add up to now.
+                Operation::Copy if finished => final_time(run, attempt.index(), target),
+                _ => time[target].map(|final_time| {
+                    final_time + changes.get(&attempt.index()).copied().unwrap_or_default()
+                }),
+            };
+            (attempt.clone(), time)
+        })
+        .collect();
+    let segment_histories: Vec<_> = run
+        .segments()
+        .iter()
+        .map(|segment| segment.segment_history().clone())
+        .collect();
+    run.clear_history();
+    for (segment, history) in run.segments_mut().iter_mut().zip(segment_histories) {
+        *segment.segment_history_mut() = history;
+    }
+    for (attempt, time) in attempts {
+        run.add_attempt_with_index(
+            time,
+            attempt.index(),
+            attempt.started(),
+            attempt.ended(),
+            attempt.pause_time(),
+        );
+    }
+
+    run.mark_as_modified();
+}
+
+#[cfg(test)]
+mod tests {
+    use livesplit_core::Segment;
+
+    use super::*;
+
+    fn seconds(seconds: f64) -> Option<TimeSpan> {
+        Some(TimeSpan::from_seconds(seconds))
+    }
+
+    fn time(real_time: Option<f64>, game_time: Option<f64>) -> Time {
+        Time::new()
+            .with_real_time(real_time.map(TimeSpan::from_seconds))
+            .with_game_time(game_time.map(TimeSpan::from_seconds))
+    }
+
+    /// Two segments with a personal best of 10 and 30 seconds and a single
+    /// attempt that is the personal best.
+    fn run() -> Run {
+        let mut run = Run::new();
+        for (name, split_time, best, segment_time) in
+            [("First", 10.0, 9.0, 10.0), ("Second", 30.0, 19.0, 20.0)]
+        {
+            let mut segment = Segment::new(name);
+            segment.set_personal_best_split_time(time(Some(split_time), None));
+            segment.set_best_segment_time(time(Some(best), None));
+            segment
+                .segment_history_mut()
+                .insert(1, time(Some(segment_time), None));
+            run.push_segment(segment);
+        }
+        run.add_attempt_with_index(time(Some(30.0), None), 1, None, None, None);
+        run
+    }
+
+    fn real_times(run: &Run, f: impl Fn(&Segment) -> Time) -> Vec<Option<TimeSpan>> {
+        run.segments()
+            .iter()
+            .map(|segment| f(segment).real_time)
+            .collect()
+    }
+
+    #[test]
+    fn parses_factors_and_ratios() {
+        assert_eq!(parse_factor("1.2"), Some(1.2));
+        assert_eq!(parse_factor(" 60 / 50 "), Some(1.2));
+        assert_eq!(parse_factor("2"), Some(2.0));
+        assert_eq!(parse_factor("0"), None);
+        assert_eq!(parse_factor("-1"), None);
+        assert_eq!(parse_factor("1/0"), None);
+        assert_eq!(parse_factor("0/0"), None);
+        assert_eq!(parse_factor("fast"), None);
+        assert_eq!(parse_factor(""), None);
+    }
+
+    #[test]
+    fn shift_moves_following_splits() {
+        let mut run = run();
+        apply(
+            &mut run,
+            &[true, false],
+            Operation::Shift(TimeSpan::from_seconds(1.0)),
+            TimingMethod::RealTime,
+        );
+
+        assert_eq!(
+            real_times(&run, |s| s.personal_best_split_time()),
+            [seconds(11.0), seconds(31.0)]
+        );
+        assert_eq!(
+            real_times(&run, |s| s.best_segment_time()),
+            [seconds(10.0), seconds(19.0)]
+        );
+        assert_eq!(
+            real_times(&run, |s| s.segment_history().get(1).unwrap()),
+            [seconds(11.0), seconds(20.0)]
+        );
+        assert_eq!(run.attempt_history()[0].time().real_time, seconds(31.0));
+        assert_eq!(run.attempt_history()[0].index(), 1);
+    }
+
+    #[test]
+    fn scale_changes_every_selected_time() {
+        let mut run = run();
+        apply(
+            &mut run,
+            &[true, true],
+            Operation::Scale(2.0),
+            TimingMethod::RealTime,
+        );
+
+        assert_eq!(
+            real_times(&run, |s| s.personal_best_split_time()),
+            [seconds(20.0), seconds(60.0)]
+        );
+        assert_eq!(
+            real_times(&run, |s| s.best_segment_time()),
+            [seconds(18.0), seconds(38.0)]
+        );
+        assert_eq!(run.attempt_history()[0].time().real_time, seconds(60.0));
+    }
+
+    #[test]
+    fn partial_copy_recomputes_final_times() {
+        let mut run = run();
+        // Only the second segment has a game time, so the attempt has none.
+        for (_, time) in run.segments_mut()[1].segment_history_mut().iter_mut() {
+            time.game_time = seconds(18.0);
+        }
+        // The second attempt didn't finish.
+        run.segments_mut()[0]
+            .segment_history_mut()
+            .insert(2, time(Some(12.0), None));
+        run.add_attempt_with_index(Time::new(), 2, None, None, None);
+
+        apply(
+            &mut run,
+            &[true, false],
+            Operation::Copy,
+            TimingMethod::GameTime,
+        );
+
+        let history = run.segments()[0].segment_history();
+        assert_eq!(history.get(1).unwrap().game_time, seconds(10.0));
+        assert_eq!(history.get(2).unwrap().game_time, seconds(12.0));
+
+        let attempts = run.attempt_history();
+        assert_eq!(attempts[0].time().game_time, seconds(28.0));
+        assert_eq!(attempts[0].time().real_time, seconds(30.0));
+        assert_eq!(attempts[1].time().game_time, None);
+    }
+
+    #[test]
+    fn copying_the_whole_run_copies_final_times() {
+        let mut run = run();
+        apply(
+            &mut run,
+            &[true, true],
+            Operation::Copy,
+            TimingMethod::GameTime,
+        );
+        assert_eq!(run.attempt_history()[0].time().game_time, seconds(30.0));
+        assert_eq!(
+            run.segments()[1].personal_best_split_time().game_time,
+            seconds(30.0)
+        );
+    }
+}
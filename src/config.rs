@@ -1,18 +1,16 @@
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, Context, Result};
 use directories::ProjectDirs;
 use druid::WindowDesc;
 use livesplit_core::{
+    hotkey::Hotkey,
     layout::{self, Layout, LayoutSettings},
-    run::{
-        parser::{composite, TimerKind},
-        saver::livesplit::save_timer,
-        LinkedLayout,
-    },
+    run::{parser::composite, saver::livesplit::save_timer, LinkedLayout},
     HotkeyConfig, HotkeySystem, Run, RunEditor, Segment, Timer, TimingMethod,
 };
 use log::error;
 use once_cell::sync::Lazy;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
 use std::{
     collections::{BTreeMap, BTreeSet},
     fs::{self, create_dir_all},
@@ -21,7 +19,16 @@ use std::{
     sync::Arc,
 };
 
-use crate::{timer_form, LayoutData, MainState};
+#[cfg(feature = "auto-splitting")]
+use crate::auto_splitter_inspector;
+use crate::{
+    app_hotkeys::{self, Action, AppHotkeys, HotkeyMode},
+    atomic_write, log_viewer,
+    notes::Notes,
+    safeguards::Safeguards,
+    splits_format::SplitsFormat,
+    timer_form, LayoutData, MainState,
+};
 
 #[derive(Default, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
@@ -31,11 +38,34 @@ pub struct Config {
     #[serde(default)]
     general: General,
     #[serde(default)]
+    layouts: Layouts,
+    #[serde(default)]
     log: Log,
     #[serde(default)]
     window: Window,
     #[serde(default)]
     hotkeys: HotkeyConfig,
+    #[serde(default)]
+    app_hotkeys: AppHotkeyConfig,
+    #[serde(default)]
+    safeguards: Safeguards,
+    #[serde(skip)]
+    read_only: bool,
+}
+
+/// Hotkeys for actions that the hotkey config of the timer doesn't cover.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct AppHotkeyConfig {
+    toggle_global_hotkeys: Option<Hotkey>,
+    #[serde(default)]
+    previous_notes: Option<Hotkey>,
+    #[serde(default)]
+    next_notes: Option<Hotkey>,
+    #[serde(default)]
+    alternates: BTreeMap<Action, Hotkey>,
+    #[serde(default)]
+    mode: HotkeyMode,
 }
 
 #[derive(Default, Deserialize, Serialize)]
@@ -43,14 +73,41 @@ pub struct Config {
 struct Splits {
     current: Option<PathBuf>,
     #[serde(skip)]
-    can_save: bool,
+    format: Option<SplitsFormat>,
+    #[serde(skip)]
+    notes: Notes,
+    /// The splits that the user already agreed to save in a format that
+    /// loses some of their data, so they aren't asked again.
+    #[serde(skip)]
+    lossy_save_confirmed: Option<PathBuf>,
     #[serde(default)]
     history: BTreeMap<Arc<str>, BTreeMap<Arc<str>, BTreeSet<Arc<Path>>>>,
+    #[serde(default)]
+    auto_splitters: BTreeMap<Arc<Path>, AutoSplitterAssociation>,
+    #[serde(default)]
+    pinned: BTreeSet<Arc<Path>>,
+    #[serde(default)]
+    library_folders: BTreeSet<PathBuf>,
+    /// Every splits file that was opened or saved, for the splits library.
+    /// Unlike the history, this isn't cleared along with the recent files.
+    #[serde(default)]
+    library: BTreeSet<Arc<Path>>,
+}
+
+/// The auto splitter to load for a splits file. It's a struct rather than just
+/// the path, so the settings can be added without breaking existing configs.
+// TODO: Store the auto splitter's settings once the auto splitting runtime
+// lets us read and change them. It only takes the path of the script so far.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct AutoSplitterAssociation {
+    path: PathBuf,
 }
 
 impl Splits {
     fn add_to_history(&mut self, run: &Run) {
         if let Some(current) = &self.current {
+            self.library.insert(current.as_path().into());
             self.history
                 .entry(run.game_name().into())
                 .or_default()
@@ -66,13 +123,30 @@ impl Splits {
 
     fn remove_from_history(&mut self) {
         if let Some(current) = self.current.as_deref() {
-            self.history.retain(|_, categories| {
-                categories.retain(|_, paths| {
-                    paths.remove(current);
-                    !paths.is_empty()
-                });
-                !categories.is_empty()
+            self.retain_history(|path| path != current);
+        }
+    }
+
+    fn retain_history(&mut self, mut f: impl FnMut(&Path) -> bool) {
+        self.history.retain(|_, categories| {
+            categories.retain(|_, paths| {
+                paths.retain(|path| f(path));
+                !paths.is_empty()
             });
+            !categories.is_empty()
+        });
+    }
+
+    fn auto_splitter(&self) -> Option<&Path> {
+        let association = self.auto_splitters.get(self.current.as_deref()?)?;
+        Some(&association.path)
+    }
+
+    fn move_auto_splitter(&mut self, new_path: &Path) {
+        if let Some(current) = self.current.as_deref() {
+            if let Some(association) = self.auto_splitters.remove(current) {
+                self.auto_splitters.insert(new_path.into(), association);
+            }
         }
     }
 }
@@ -94,16 +168,54 @@ struct General {
     timing_method: Option<TimingMethod>,
     comparison: Option<String>,
     auto_splitter: Option<PathBuf>,
+    #[serde(skip)]
+    loaded_auto_splitter: Option<PathBuf>,
 }
 
+const MAX_RECENT_LAYOUTS: usize = 10;
+
 #[derive(Default, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
-struct Log {
+struct Layouts {
+    #[serde(default)]
+    recent: Vec<Arc<Path>>,
+    #[serde(default)]
+    pinned: BTreeSet<Arc<Path>>,
+}
+
+impl Layouts {
+    fn add_to_recent(&mut self, path: &Path) {
+        self.recent.retain(|recent| &**recent != path);
+        self.recent.insert(0, path.into());
+        self.recent.truncate(MAX_RECENT_LAYOUTS);
+    }
+}
+
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Log {
     #[serde(default)]
-    enable: bool,
-    level: Option<log::LevelFilter>,
+    pub enable: bool,
+    pub level: Option<log::LevelFilter>,
     #[serde(default)]
-    clear: bool,
+    pub clear: bool,
+}
+
+/// Everything that can be changed in the settings editor.
+pub struct Settings {
+    pub hotkeys: HotkeyConfig,
+    pub app_hotkey_primaries: BTreeMap<Action, Hotkey>,
+    pub alternate_hotkeys: BTreeMap<Action, Hotkey>,
+    pub hotkey_mode: HotkeyMode,
+    pub timing_method: TimingMethod,
+    pub comparison: String,
+    pub window_size: (f64, f64),
+    /// Changes to the log settings only take effect after restarting, as the
+    /// logger can only be set up once.
+    pub log: Log,
+    pub safeguards: Safeguards,
+    #[cfg(feature = "auto-splitting")]
+    pub auto_splitter: Option<PathBuf>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -131,45 +243,238 @@ static CONFIG_PATH: Lazy<PathBuf> = Lazy::new(|| {
 
 impl Config {
     pub fn load() -> Self {
-        Self::parse().unwrap_or_default()
+        let buf = match fs::read(CONFIG_PATH.as_path()) {
+            Ok(buf) => buf,
+            Err(_) => return Self::default(),
+        };
+        let mut config = match serde_yaml::from_slice(&buf) {
+            Ok(config) => config,
+            Err(error) => Self::recover(&buf, error),
+        };
+        config.prune_missing_files();
+        config
+    }
+
+    /// Removes the files that no longer exist from the recent and pinned
+    /// files, the splits library and the auto splitter associations, and lets
+    /// the user know about them.
+    fn prune_missing_files(&mut self) {
+        let mut missing = BTreeSet::new();
+        let mut exists = |path: &Path| {
+            let exists = path.exists();
+            if !exists {
+                missing.insert(path.to_path_buf());
+            }
+            exists
+        };
+
+        self.splits.retain_history(&mut exists);
+        self.splits.pinned.retain(|path| exists(path));
+        self.splits.library.retain(|path| exists(path));
+        self.splits.auto_splitters.retain(|path, _| exists(path));
+        self.layouts.recent.retain(|path| exists(path));
+        self.layouts.pinned.retain(|path| exists(path));
+
+        if missing.is_empty() {
+            return;
+        }
+
+        let mut text = String::from(
+            "The following files no longer exist and were removed from the recent files:",
+        );
+        for path in &missing {
+            text.push_str("\n- ");
+            text.push_str(&path.to_string_lossy());
+        }
+        let _ = native_dialog::MessageDialog::new()
+            .set_type(native_dialog::MessageType::Info)
+            .set_title("Missing Files")
+            .set_text(&text)
+            .show_alert();
+
+        self.save_config();
+    }
+
+    /// Loads as much as possible from a config that failed to parse. The
+    /// broken file is backed up first, as the next save overwrites it. If that
+    /// isn't possible, the config is never saved, so nothing gets lost.
+    fn recover(buf: &[u8], error: serde_yaml::Error) -> Self {
+        let mut problems = Vec::new();
+        let mut config = match serde_yaml::from_slice(buf) {
+            Ok(Value::Mapping(sections)) => Self {
+                splits: lenient(&sections, "splits", &mut problems),
+                general: lenient(&sections, "general", &mut problems),
+                layouts: lenient(&sections, "layouts", &mut problems),
+                log: lenient(&sections, "log", &mut problems),
+                window: lenient(&sections, "window", &mut problems),
+                hotkeys: lenient(&sections, "hotkeys", &mut problems),
+                app_hotkeys: lenient(&sections, "app-hotkeys", &mut problems),
+                safeguards: lenient(&sections, "safeguards", &mut problems),
+                read_only: false,
+            },
+            _ => Self::default(),
+        };
+
+        let mut message = match error.location() {
+            Some(location) => format!(
+                "The configuration file is invalid at line {}, column {}:\n{error}",
+                location.line(),
+                location.column(),
+            ),
+            None => format!("The configuration file is invalid:\n{error}"),
+        };
+
+        if !problems.is_empty() {
+            message.push_str("\n\nThe following settings were reset to their defaults:");
+            for problem in &problems {
+                message.push_str("\n- ");
+                message.push_str(problem);
+            }
+        }
+
+        let backup_path = CONFIG_PATH.with_extension("yml.broken");
+        match fs::write(&backup_path, buf) {
+            Ok(()) => {
+                message.push_str(&format!(
+                    "\n\nThe original file was backed up to \"{}\".",
+                    backup_path.display(),
+                ));
+            }
+            Err(e) => {
+                config.read_only = true;
+                message.push_str(&format!(
+                    "\n\nThe original file couldn't be backed up ({e}), so no settings will be saved until LiveSplit One is restarted.",
+                ));
+            }
+        }
+
+        show_error(anyhow!(message));
+        config
     }
 
     fn save_config(&self) -> Option<()> {
+        if self.read_only {
+            return None;
+        }
         create_dir_all(CONFIG_PATH.parent()?).ok()?;
         self.serialize()
     }
 
-    fn parse() -> Option<Self> {
-        let buf = fs::read(CONFIG_PATH.as_path()).ok()?;
-        serde_yaml::from_slice(&buf).ok()
-    }
-
     fn serialize(&self) -> Option<()> {
         let buf = serde_yaml::to_string(self).ok()?;
-        fs::write(CONFIG_PATH.as_path(), buf).ok()
+        atomic_write::write(CONFIG_PATH.as_path(), buf).ok()
     }
 
     pub fn splits_history(&self) -> &BTreeMap<Arc<str>, BTreeMap<Arc<str>, BTreeSet<Arc<Path>>>> {
         &self.splits.history
     }
 
-    fn parse_run(&self) -> Option<(Run, bool)> {
+    pub fn pinned_splits(&self) -> &BTreeSet<Arc<Path>> {
+        &self.splits.pinned
+    }
+
+    /// The splits files that were ever opened or saved.
+    pub fn library_splits(&self) -> &BTreeSet<Arc<Path>> {
+        &self.splits.library
+    }
+
+    /// The folders the splits library scans for splits files.
+    pub fn library_folders(&self) -> &BTreeSet<PathBuf> {
+        &self.splits.library_folders
+    }
+
+    pub fn add_library_folder(&mut self, folder: PathBuf) {
+        if self.splits.library_folders.insert(folder) {
+            self.save_config();
+        }
+    }
+
+    pub fn recent_layouts(&self) -> &[Arc<Path>] {
+        &self.layouts.recent
+    }
+
+    pub fn pinned_layouts(&self) -> &BTreeSet<Arc<Path>> {
+        &self.layouts.pinned
+    }
+
+    /// Returns whether the current splits are pinned, or `None` if they aren't
+    /// stored in a file.
+    pub fn are_splits_pinned(&self) -> Option<bool> {
+        let current = self.splits.current.as_deref()?;
+        Some(self.splits.pinned.contains(current))
+    }
+
+    /// Returns whether the current layout is pinned, or `None` if it isn't
+    /// stored in a file.
+    pub fn is_layout_pinned(&self) -> Option<bool> {
+        let current = self.general.layout.as_deref()?;
+        Some(self.layouts.pinned.contains(current))
+    }
+
+    pub fn toggle_pin_splits(&mut self) {
+        if let Some(current) = self.splits.current.as_deref() {
+            if !self.splits.pinned.remove(current) {
+                self.splits.pinned.insert(current.into());
+            }
+            self.save_config();
+        }
+    }
+
+    pub fn toggle_pin_layout(&mut self) {
+        if let Some(current) = self.general.layout.as_deref() {
+            if !self.layouts.pinned.remove(current) {
+                self.layouts.pinned.insert(current.into());
+            }
+            self.save_config();
+        }
+    }
+
+    /// Clears the recently opened splits. Pinned splits are kept. The auto
+    /// splitters associated with splits that no longer exist are forgotten as
+    /// well.
+    pub fn clear_recent_splits(&mut self) {
+        self.splits.history.clear();
+        self.splits.auto_splitters.retain(|path, _| path.exists());
+        self.save_config();
+    }
+
+    /// Clears the recently opened layouts. Pinned layouts are kept.
+    pub fn clear_recent_layouts(&mut self) {
+        self.layouts.recent.clear();
+        self.save_config();
+    }
+
+    /// Removes a file that went missing while the application is running from
+    /// the recent and pinned files. The ones missing on startup are removed
+    /// right away.
+    pub fn forget_missing_file(&mut self, missing: &Path) {
+        self.splits.retain_history(|path| path != missing);
+        self.splits.pinned.remove(missing);
+        self.splits.library.remove(missing);
+        self.splits.auto_splitters.remove(missing);
+        self.layouts.recent.retain(|path| &**path != missing);
+        self.layouts.pinned.remove(missing);
+        self.save_config();
+    }
+
+    fn parse_run(&self) -> Option<(Run, Option<SplitsFormat>, Notes)> {
         let path = self.splits.current.clone()?;
         let file = fs::read(&path).ok()?;
         let parsed_run = composite::parse(&file, Some(&path)).ok()?;
-        let run = parsed_run.run;
-        let can_save = parsed_run.kind == TimerKind::LiveSplit;
-        Some((run, can_save))
+        let format = SplitsFormat::detect(&parsed_run.kind);
+        Some((parsed_run.run, format, Notes::load(&path)))
     }
 
     pub fn parse_run_or_default(&mut self) -> Run {
         match self.parse_run() {
-            Some((run, can_save)) => {
-                self.splits.can_save = can_save;
+            Some((run, format, notes)) => {
+                self.splits.format = format;
+                self.splits.notes = notes;
                 run
             }
             None => {
-                self.splits.can_save = false;
+                self.splits.format = None;
+                self.splits.notes = Notes::default();
                 default_run()
             }
         }
@@ -222,8 +527,53 @@ impl Config {
     }
 
     // TODO: Just directly construct the HotkeySystem from the config.
-    pub fn configure_hotkeys(&self, hotkeys: &mut HotkeySystem) {
-        hotkeys.set_config(self.hotkeys).ok();
+    /// Returns the hotkeys that couldn't be registered globally.
+    pub fn configure_hotkeys(
+        &self,
+        hotkeys: &mut HotkeySystem,
+        app_hotkeys: &mut AppHotkeys,
+    ) -> Vec<Hotkey> {
+        let handled_by_system =
+            app_hotkeys::configure_system(hotkeys, self.hotkeys, self.app_hotkeys.mode);
+        app_hotkeys.configure(
+            &self.hotkeys,
+            &self.app_hotkey_primaries(),
+            &self.app_hotkeys.alternates,
+            self.app_hotkeys.mode,
+            handled_by_system,
+        )
+    }
+
+    pub fn safeguards(&self) -> Safeguards {
+        self.safeguards
+    }
+
+    pub fn hotkeys(&self) -> HotkeyConfig {
+        self.hotkeys
+    }
+
+    /// The hotkeys of the actions that the hotkey config of the timer doesn't
+    /// cover.
+    pub fn app_hotkey_primaries(&self) -> BTreeMap<Action, Hotkey> {
+        [
+            (Action::PreviousNotes, self.app_hotkeys.previous_notes),
+            (Action::NextNotes, self.app_hotkeys.next_notes),
+            (
+                Action::ToggleGlobalHotkeys,
+                self.app_hotkeys.toggle_global_hotkeys,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(action, hotkey)| Some((action, hotkey?)))
+        .collect()
+    }
+
+    pub fn alternate_hotkeys(&self) -> &BTreeMap<Action, Hotkey> {
+        &self.app_hotkeys.alternates
+    }
+
+    pub fn hotkey_mode(&self) -> HotkeyMode {
+        self.app_hotkeys.mode
     }
 
     pub fn configure_timer(&self, timer: &mut Timer) {
@@ -235,15 +585,36 @@ impl Config {
         }
     }
 
-    pub fn set_hotkeys(&mut self, hotkeys: HotkeyConfig) {
-        self.hotkeys = hotkeys;
+    /// Takes over everything from the settings editor, saving the
+    /// configuration only once.
+    pub fn apply_settings(&mut self, settings: Settings) {
+        self.hotkeys = settings.hotkeys;
+        let primaries = &settings.app_hotkey_primaries;
+        self.app_hotkeys.previous_notes = primaries.get(&Action::PreviousNotes).copied();
+        self.app_hotkeys.next_notes = primaries.get(&Action::NextNotes).copied();
+        self.app_hotkeys.toggle_global_hotkeys =
+            primaries.get(&Action::ToggleGlobalHotkeys).copied();
+        self.app_hotkeys.alternates = settings.alternate_hotkeys;
+        self.app_hotkeys.mode = settings.hotkey_mode;
+        self.general.timing_method = Some(settings.timing_method);
+        self.general.comparison = Some(settings.comparison);
+        let (width, height) = settings.window_size;
+        self.window.width = width;
+        self.window.height = height;
+        self.log = settings.log;
+        self.safeguards = settings.safeguards;
+        #[cfg(feature = "auto-splitting")]
+        {
+            self.general.auto_splitter = settings.auto_splitter;
+        }
         self.save_config();
     }
 
     pub fn new_splits(&mut self, timer: &mut Timer) {
         timer.set_run(default_run()).map_err(drop).unwrap();
-        self.splits.can_save = false;
+        self.splits.format = None;
         self.splits.current = None;
+        self.splits.notes = Notes::default();
         self.save_config();
     }
 
@@ -259,7 +630,8 @@ impl Config {
             "The splits can't be used with the timer because they don't contain a single segment.",
         )?;
 
-        self.splits.can_save = run.kind == TimerKind::LiveSplit;
+        self.splits.format = SplitsFormat::detect(&run.kind);
+        self.splits.notes = Notes::load(&path);
         self.splits.current = Some(path);
         self.splits.add_to_history(timer.run());
 
@@ -278,14 +650,39 @@ impl Config {
     }
 
     pub fn can_directly_save_splits(&self) -> bool {
-        self.splits.current.is_some() && self.splits.can_save
+        self.splits.current.is_some() && self.splits.format.is_some()
+    }
+
+    /// Describes what gets lost when saving the splits in the format they were
+    /// loaded from, if that isn't the LiveSplit format. This is only reported
+    /// until the user confirms saving the file anyway.
+    pub fn unsupported_splits_data(&self, run: &Run) -> Option<String> {
+        let format = self.splits.format?;
+        if self.splits.current.is_some() && self.splits.lossy_save_confirmed == self.splits.current
+        {
+            return None;
+        }
+        let unsupported = format.unsupported_data(run);
+        if unsupported.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "These splits are stored in the {} format, which can't store the following: {}.",
+            format.name(),
+            unsupported.join(", "),
+        ))
+    }
+
+    /// Remembers that the current splits may be saved in their format, even
+    /// though it loses some of their data.
+    pub fn confirm_lossy_save(&mut self) {
+        self.splits.lossy_save_confirmed = self.splits.current.clone();
     }
 
     pub fn save_splits(&mut self, timer: &mut Timer) -> Result<()> {
-        if let Some(path) = &self.splits.current {
-            let mut buf = String::new();
-            save_timer(timer, &mut buf).context("Failed saving the splits.")?;
-            fs::write(path, &buf).context("Failed writing the file.")?;
+        if let (Some(path), Some(format)) = (&self.splits.current, self.splits.format) {
+            let buf = format.write(timer)?;
+            atomic_write::write(path, &buf).context("Failed writing the file.")?;
             timer.mark_as_unmodified();
 
             self.splits.remove_from_history();
@@ -299,13 +696,15 @@ impl Config {
     pub fn save_splits_as(&mut self, timer: &mut Timer, path: PathBuf) -> Result<()> {
         let mut buf = String::new();
         save_timer(timer, &mut buf).context("Failed saving the splits.")?;
-        fs::write(&path, &buf).context("Failed writing the file.")?;
+        atomic_write::write(&path, &buf).context("Failed writing the file.")?;
         timer.mark_as_unmodified();
+        self.splits.notes.save(&path)?;
 
-        if !self.splits.can_save {
+        if self.splits.format.is_none() {
             self.splits.remove_from_history();
         }
-        self.splits.can_save = true;
+        self.splits.move_auto_splitter(&path);
+        self.splits.format = Some(SplitsFormat::LiveSplit);
         self.splits.current = Some(path);
         self.splits.add_to_history(timer.run());
 
@@ -313,6 +712,44 @@ impl Config {
         Ok(())
     }
 
+    pub fn notes(&self) -> &Notes {
+        &self.splits.notes
+    }
+
+    /// Replaces the notes of the current splits. They are saved right away if
+    /// the splits are stored in a file, otherwise once they get saved.
+    pub fn set_notes(&mut self, notes: Notes) -> Result<()> {
+        self.splits.notes = notes;
+        if let Some(path) = &self.splits.current {
+            self.splits.notes.save(path)?;
+        }
+        Ok(())
+    }
+
+    pub fn auto_splitter_for_current_splits(&self) -> Option<&Path> {
+        self.splits.auto_splitter()
+    }
+
+    pub fn can_associate_auto_splitter(&self) -> bool {
+        self.splits.current.is_some()
+    }
+
+    pub fn set_auto_splitter_for_current_splits(&mut self, path: Option<PathBuf>) {
+        if let Some(current) = self.splits.current.as_deref() {
+            match path {
+                Some(path) => {
+                    self.splits
+                        .auto_splitters
+                        .insert(current.into(), AutoSplitterAssociation { path });
+                }
+                None => {
+                    self.splits.auto_splitters.remove(current);
+                }
+            }
+            self.save_config();
+        }
+    }
+
     pub fn link_layout(&self, run_editor: &mut RunEditor) {
         run_editor.set_linked_layout(Some(
             match &self.general.layout {
@@ -345,6 +782,7 @@ impl Config {
         let (layout, can_save) = Self::parse_layout_with_path(path)?;
         self.general.can_save_layout = can_save;
         self.general.layout = Some(path.into());
+        self.layouts.add_to_recent(path);
         layout_data.layout = layout;
         layout_data.is_modified = false;
 
@@ -362,7 +800,7 @@ impl Config {
             settings
                 .write_json(&mut buf)
                 .context("Failed saving the layout.")?;
-            fs::write(path, &buf).context("Failed writing the file.")?;
+            atomic_write::write(path, &buf).context("Failed writing the file.")?;
         }
         Ok(())
     }
@@ -377,10 +815,11 @@ impl Config {
         settings
             .write_json(&mut buf)
             .context("Failed saving the layout.")?;
-        fs::write(&path, &buf).context("Failed writing the file.")?;
+        atomic_write::write(&path, &buf).context("Failed writing the file.")?;
 
         timer.layout_path_changed(path.to_str());
 
+        self.layouts.add_to_recent(&path);
         self.general.can_save_layout = true;
         self.general.layout = Some(path);
         self.save_config();
@@ -401,33 +840,57 @@ impl Config {
         self.save_config();
     }
 
-    pub fn setup_logging(&self) -> Option<()> {
-        if self.log.enable {
-            let config_folder = CONFIG_PATH.parent()?;
-            create_dir_all(config_folder).ok()?;
+    pub fn layout_path(&self) -> Option<&Path> {
+        self.general.layout.as_deref()
+    }
+
+    pub fn global_auto_splitter(&self) -> Option<&Path> {
+        self.general.auto_splitter.as_deref()
+    }
+
+    pub fn window_size(&self) -> (f64, f64) {
+        (self.window.width, self.window.height)
+    }
 
-            let log_file = fs::OpenOptions::new()
-                .create(true)
-                .write(true)
-                .append(!self.log.clear)
-                .truncate(self.log.clear)
-                .open(config_folder.join("log.txt"))
-                .ok()?;
+    pub fn log(&self) -> &Log {
+        &self.log
+    }
 
+    pub fn setup_logging(&self) -> Option<()> {
+        let mut dispatch = fern::Dispatch::new().chain(
             fern::Dispatch::new()
-                .format(|out, message, record| {
-                    out.finish(format_args!(
-                        "{}[{}][{}] {}",
-                        chrono::Local::now().format("[%Y-%m-%d %H:%M:%S]"),
-                        record.target(),
-                        record.level(),
-                        message
-                    ))
-                })
-                .level(self.log.level.unwrap_or(log::LevelFilter::Warn))
-                .chain(log_file)
-                .apply()
-                .ok()?;
+                .level(log_viewer::CAPTURE_LEVEL)
+                .level_for(env!("CARGO_CRATE_NAME"), log_viewer::CAPTURE_LEVEL_OWN)
+                .chain(fern::Output::call(log_viewer::capture)),
+        );
+
+        #[cfg(feature = "auto-splitting")]
+        {
+            dispatch = dispatch.chain(
+                fern::Dispatch::new()
+                    .level(log::LevelFilter::Off)
+                    .level_for(auto_splitter_inspector::LOG_TARGET, log::LevelFilter::Trace)
+                    .chain(fern::Output::call(auto_splitter_inspector::capture_log)),
+            );
+        }
+
+        if self.log.enable {
+            if let Some(log_file) = self.open_log_file() {
+                dispatch = dispatch.chain(
+                    fern::Dispatch::new()
+                        .format(|out, message, record| {
+                            out.finish(format_args!(
+                                "{}[{}][{}] {}",
+                                chrono::Local::now().format("[%Y-%m-%d %H:%M:%S]"),
+                                record.target(),
+                                record.level(),
+                                message
+                            ))
+                        })
+                        .level(self.log.level.unwrap_or(log::LevelFilter::Warn))
+                        .chain(log_file),
+                );
+            }
 
             #[cfg(not(debug_assertions))]
             {
@@ -436,7 +899,21 @@ impl Config {
                 }));
             }
         }
-        Some(())
+
+        dispatch.apply().ok()
+    }
+
+    fn open_log_file(&self) -> Option<fs::File> {
+        let config_folder = CONFIG_PATH.parent()?;
+        create_dir_all(config_folder).ok()?;
+
+        fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(!self.log.clear)
+            .truncate(self.log.clear)
+            .open(config_folder.join("log.txt"))
+            .ok()
     }
 
     pub fn build_window(&self) -> WindowDesc<MainState> {
@@ -449,17 +926,73 @@ impl Config {
         // .topmost(true)
     }
 
+    /// Loads the auto splitter associated with the current splits, falling back
+    /// to the globally configured one. The script is only reloaded if it
+    /// differs from the one that is currently loaded.
     #[cfg(feature = "auto-splitting")]
-    pub fn maybe_load_auto_splitter(&self, runtime: &livesplit_core::auto_splitting::Runtime) {
-        if let Some(auto_splitter) = &self.general.auto_splitter {
-            if let Err(e) = runtime.load_script_blocking(auto_splitter.clone()) {
+    pub fn maybe_load_auto_splitter(&mut self, runtime: &livesplit_core::auto_splitting::Runtime) {
+        let auto_splitter = self
+            .splits
+            .auto_splitter()
+            .or(self.general.auto_splitter.as_deref())
+            .map(Path::to_path_buf);
+
+        if auto_splitter == self.general.loaded_auto_splitter {
+            return;
+        }
+
+        if self.general.loaded_auto_splitter.take().is_some() {
+            if let Err(e) = runtime.unload_script_blocking() {
+                log::error!("Auto Splitter failed to unload: {}", e);
+            }
+        }
+
+        if let Some(auto_splitter) = auto_splitter {
+            match runtime.load_script_blocking(auto_splitter.clone()) {
+                Ok(()) => self.general.loaded_auto_splitter = Some(auto_splitter),
                 // TODO: Error chain
-                log::error!("Auto Splitter failed to load: {}", e);
+                Err(e) => log::error!("Auto Splitter failed to load: {}", e),
             }
         }
     }
 }
 
+/// Deserializes a section of the config, dropping the fields that fail to
+/// deserialize, so the remaining ones can still be used.
+fn lenient<T: Default + DeserializeOwned>(
+    sections: &Mapping,
+    section: &str,
+    problems: &mut Vec<String>,
+) -> T {
+    let fields = match sections.get(section) {
+        None => return T::default(),
+        Some(Value::Mapping(fields)) => fields,
+        Some(_) => {
+            problems.push(format!("{section}: Expected a section."));
+            return T::default();
+        }
+    };
+
+    if let Ok(value) = serde_yaml::from_value(Value::Mapping(fields.clone())) {
+        return value;
+    }
+
+    let mut valid = Mapping::new();
+    for (key, value) in fields {
+        let mut candidate = valid.clone();
+        candidate.insert(key.clone(), value.clone());
+        match serde_yaml::from_value::<T>(Value::Mapping(candidate.clone())) {
+            Ok(_) => valid = candidate,
+            Err(e) => {
+                let key = key.as_str().unwrap_or("?");
+                problems.push(format!("{section}.{key}: {e}"));
+            }
+        }
+    }
+
+    serde_yaml::from_value(Value::Mapping(valid)).unwrap_or_default()
+}
+
 fn default_run() -> Run {
     let mut run = Run::new();
     run.push_segment(Segment::new("Time"));
@@ -0,0 +1,348 @@
+use std::{fmt::Write, path::Path};
+
+use anyhow::{Context, Result};
+use druid::FileSpec;
+use livesplit_core::{
+    timing::formatter::{Complete, TimeFormatter},
+    Run, Time, TimeSpan, TimingMethod,
+};
+use serde_json::{json, Value};
+
+use crate::atomic_write;
+
+/// The formats the splits can be exported to. Unlike saving, exporting doesn't
+/// change which file the splits are associated with.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    SplitsIo,
+    RouteSheet,
+}
+
+impl Format {
+    pub fn title(self) -> &'static str {
+        match self {
+            Format::Csv => "Export Splits as CSV",
+            Format::SplitsIo => "Export Splits as Splits.io JSON",
+            Format::RouteSheet => "Export Route Sheet",
+        }
+    }
+
+    pub fn file_spec(self) -> FileSpec {
+        match self {
+            Format::Csv => FileSpec {
+                name: "CSV",
+                extensions: &["csv"],
+            },
+            Format::SplitsIo => FileSpec {
+                name: "Splits.io JSON",
+                extensions: &["json"],
+            },
+            Format::RouteSheet => FileSpec {
+                name: "Text",
+                extensions: &["txt"],
+            },
+        }
+    }
+}
+
+pub fn export(format: Format, run: &Run, timing_method: TimingMethod, path: &Path) -> Result<()> {
+    let contents = match format {
+        Format::Csv => csv(run),
+        Format::SplitsIo => splits_io(run),
+        Format::RouteSheet => route_sheet(run, timing_method),
+    };
+    atomic_write::write(path, contents).context("Failed writing the file.")
+}
+
+fn format_time(time: Option<TimeSpan>) -> String {
+    time.map(|time| Complete::new().format(time).to_string())
+        .unwrap_or_default()
+}
+
+fn csv_field(buf: &mut String, field: &str) {
+    if field.contains([',', '"', '\n', '\r']) {
+        buf.push('"');
+        buf.push_str(&field.replace('"', "\"\""));
+        buf.push('"');
+    } else {
+        buf.push_str(field);
+    }
+}
+
+fn csv_row<'a>(buf: &mut String, fields: impl IntoIterator<Item = &'a str>) {
+    for (index, field) in fields.into_iter().enumerate() {
+        if index != 0 {
+            buf.push(',');
+        }
+        csv_field(buf, field);
+    }
+    buf.push_str("\r\n");
+}
+
+/// One row per segment with the best segment times and the split times of all
+/// the comparisons, for both timing methods.
+fn csv(run: &Run) -> String {
+    let comparisons: Vec<&str> = run.comparisons().collect();
+
+    let mut header = vec![
+        String::from("Segment"),
+        String::from("Best Segment (Real Time)"),
+        String::from("Best Segment (Game Time)"),
+    ];
+    for comparison in &comparisons {
+        header.push(format!("{comparison} (Real Time)"));
+        header.push(format!("{comparison} (Game Time)"));
+    }
+
+    let mut buf = String::new();
+    csv_row(&mut buf, header.iter().map(String::as_str));
+
+    for segment in run.segments() {
+        let best_segment = segment.best_segment_time();
+        let mut row = vec![
+            segment.name().to_owned(),
+            format_time(best_segment.real_time),
+            format_time(best_segment.game_time),
+        ];
+        for comparison in &comparisons {
+            let time = segment.comparison(comparison);
+            row.push(format_time(time.real_time));
+            row.push(format_time(time.game_time));
+        }
+        csv_row(&mut buf, row.iter().map(String::as_str));
+    }
+
+    buf
+}
+
+fn splits_io_time(time: Time) -> Value {
+    json!({
+        "realtimeMS": time.real_time.map(|t| t.total_milliseconds().round() as i64),
+        "gametimeMS": time.game_time.map(|t| t.total_milliseconds().round() as i64),
+    })
+}
+
+/// The generic exchange format described at
+/// https://github.com/glacials/splits-io/tree/main/public/schema
+pub fn splits_io(run: &Run) -> String {
+    let segments: Vec<Value> = run
+        .segments()
+        .iter()
+        .map(|segment| {
+            let split_time = segment.personal_best_split_time();
+            json!({
+                "name": segment.name(),
+                "endedAt": splits_io_time(split_time),
+                "bestDuration": splits_io_time(segment.best_segment_time()),
+                "isSkipped": split_time.real_time.is_none() && split_time.game_time.is_none(),
+            })
+        })
+        .collect();
+
+    let last_split_time = run
+        .segments()
+        .last()
+        .map(|segment| segment.personal_best_split_time())
+        .unwrap_or_default();
+
+    let document = json!({
+        "_schemaVersion": "v1.0.1",
+        "timer": {
+            "shortname": "livesplit-one",
+            "longname": "LiveSplit One",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "attempts": {
+            "total": run.attempt_count(),
+        },
+        "game": {
+            "longname": run.game_name(),
+        },
+        "category": {
+            "longname": run.category_name(),
+        },
+        "endedAt": splits_io_time(last_split_time),
+        "segments": segments,
+    });
+
+    serde_json::to_string_pretty(&document).unwrap_or_default()
+}
+
+/// A plain text overview of the route with the personal best split times and
+/// the best segments, meant for printing or reading next to the game.
+fn route_sheet(run: &Run, timing_method: TimingMethod) -> String {
+    let mut buf = String::new();
+    let _ = writeln!(
+        buf,
+        "{} - {}",
+        run.game_name(),
+        run.extended_category_name(false, false, true)
+    );
+
+    let personal_best = run
+        .segments()
+        .last()
+        .and_then(|segment| segment.personal_best_split_time()[timing_method]);
+    let _ = writeln!(
+        buf,
+        "Personal Best: {}",
+        personal_best.map_or_else(|| String::from("None"), |time| format_time(Some(time)))
+    );
+    buf.push('\n');
+
+    let rows: Vec<[String; 2]> = run
+        .segments()
+        .iter()
+        .map(|segment| {
+            [
+                format_time(segment.personal_best_split_time()[timing_method]),
+                format_time(segment.best_segment_time()[timing_method]),
+            ]
+        })
+        .collect();
+
+    let name_width = run
+        .segments()
+        .iter()
+        .map(|segment| segment.name().chars().count())
+        .max()
+        .unwrap_or_default()
+        .max("Segment".len());
+    let number_width = run.len().to_string().len();
+    let time_width = rows
+        .iter()
+        .flatten()
+        .map(String::len)
+        .max()
+        .unwrap_or_default()
+        .max("Best Segment".len());
+
+    let _ = writeln!(
+        buf,
+        "{:number_width$}  {:name_width$}  {:>time_width$}  {:>time_width$}",
+        "", "Segment", "PB Split", "Best Segment"
+    );
+
+    for (index, (segment, [split_time, best_segment])) in
+        run.segments().iter().zip(&rows).enumerate()
+    {
+        let _ = writeln!(
+            buf,
+            "{:>number_width$}  {:name_width$}  {:>time_width$}  {:>time_width$}",
+            index + 1,
+            segment.name(),
+            split_time,
+            best_segment,
+        );
+    }
+
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use livesplit_core::Segment;
+
+    use super::*;
+
+    fn time(real_time: Option<f64>, game_time: Option<f64>) -> Time {
+        Time::new()
+            .with_real_time(real_time.map(TimeSpan::from_seconds))
+            .with_game_time(game_time.map(TimeSpan::from_seconds))
+    }
+
+    /// A run with a skipped split, game time and a missing game time gold.
+    fn run() -> Run {
+        let mut run = Run::new();
+        run.set_game_name("Game");
+        run.set_category_name("Any%");
+
+        let mut first = Segment::new("First, \"Part\" 1");
+        first.set_personal_best_split_time(time(Some(60.0), Some(55.0)));
+        first.set_best_segment_time(time(Some(58.5), None));
+        run.push_segment(first);
+
+        run.push_segment(Segment::new("Skipped"));
+
+        let mut last = Segment::new("Last");
+        last.set_personal_best_split_time(time(Some(150.0), Some(140.0)));
+        last.set_best_segment_time(time(Some(80.0), Some(75.0)));
+        run.push_segment(last);
+
+        run
+    }
+
+    #[test]
+    fn csv_rows() {
+        assert_eq!(
+            csv(&run()),
+            "Segment,Best Segment (Real Time),Best Segment (Game Time),\
+            Personal Best (Real Time),Personal Best (Game Time)\r\n\
+            \"First, \"\"Part\"\" 1\",00:00:58.5000000,,00:01:00.0000000,00:00:55.0000000\r\n\
+            Skipped,,,,\r\n\
+            Last,00:01:20.0000000,00:01:15.0000000,00:02:30.0000000,00:02:20.0000000\r\n",
+        );
+    }
+
+    #[test]
+    fn splits_io_document() {
+        let document: Value = serde_json::from_str(&splits_io(&run())).unwrap();
+
+        assert_eq!(document["game"]["longname"], "Game");
+        assert_eq!(document["category"]["longname"], "Any%");
+        assert_eq!(document["attempts"]["total"], 0);
+        assert_eq!(
+            document["endedAt"],
+            json!({ "realtimeMS": 150000, "gametimeMS": 140000 })
+        );
+
+        let segments = document["segments"].as_array().unwrap();
+        assert_eq!(segments.len(), 3);
+
+        assert_eq!(segments[0]["name"], "First, \"Part\" 1");
+        assert_eq!(
+            segments[0]["endedAt"],
+            json!({ "realtimeMS": 60000, "gametimeMS": 55000 })
+        );
+        assert_eq!(
+            segments[0]["bestDuration"],
+            json!({ "realtimeMS": 58500, "gametimeMS": null })
+        );
+        assert_eq!(segments[0]["isSkipped"], false);
+
+        assert_eq!(segments[1]["isSkipped"], true);
+        assert_eq!(
+            segments[1]["endedAt"],
+            json!({ "realtimeMS": null, "gametimeMS": null })
+        );
+
+        assert_eq!(segments[2]["isSkipped"], false);
+    }
+
+    #[test]
+    fn route_sheet_columns() {
+        let sheet = route_sheet(&run(), TimingMethod::GameTime);
+        let lines: Vec<&str> = sheet.lines().map(str::trim_end).collect();
+        assert_eq!(
+            lines,
+            [
+                "Game - Any%",
+                "Personal Best: 00:02:20.0000000",
+                "",
+                "   Segment                  PB Split      Best Segment",
+                "1  First, \"Part\" 1  00:00:55.0000000",
+                "2  Skipped",
+                "3  Last             00:02:20.0000000  00:01:15.0000000",
+            ]
+        );
+    }
+
+    #[test]
+    fn route_sheet_without_personal_best() {
+        let mut run = run();
+        run.segments_mut()[2].set_personal_best_split_time(Time::default());
+        let sheet = route_sheet(&run, TimingMethod::RealTime);
+        assert_eq!(sheet.lines().nth(1), Some("Personal Best: None"));
+    }
+}
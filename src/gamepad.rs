@@ -0,0 +1,261 @@
+use std::{
+    collections::HashSet,
+    io,
+    os::unix::io::AsRawFd,
+    path::PathBuf,
+    sync::{
+        atomic::{self, AtomicBool},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use evdev::{Device, InputEventKind, Key};
+use livesplit_core::hotkey::KeyCode;
+
+/// The buttons that map to the gamepad key codes, in the order of the
+/// standard gamepad layout. Joysticks number their buttons instead of naming
+/// them, so they get the codes after the ones of the gamepads. There are only
+/// 20 gamepad key codes, so only their first three buttons can be used.
+const BUTTONS: [(Key, KeyCode); 20] = [
+    (Key::BTN_SOUTH, KeyCode::Gamepad0),
+    (Key::BTN_EAST, KeyCode::Gamepad1),
+    (Key::BTN_WEST, KeyCode::Gamepad2),
+    (Key::BTN_NORTH, KeyCode::Gamepad3),
+    (Key::BTN_TL, KeyCode::Gamepad4),
+    (Key::BTN_TR, KeyCode::Gamepad5),
+    (Key::BTN_TL2, KeyCode::Gamepad6),
+    (Key::BTN_TR2, KeyCode::Gamepad7),
+    (Key::BTN_SELECT, KeyCode::Gamepad8),
+    (Key::BTN_START, KeyCode::Gamepad9),
+    (Key::BTN_THUMBL, KeyCode::Gamepad10),
+    (Key::BTN_THUMBR, KeyCode::Gamepad11),
+    (Key::BTN_DPAD_UP, KeyCode::Gamepad12),
+    (Key::BTN_DPAD_DOWN, KeyCode::Gamepad13),
+    (Key::BTN_DPAD_LEFT, KeyCode::Gamepad14),
+    (Key::BTN_DPAD_RIGHT, KeyCode::Gamepad15),
+    (Key::BTN_MODE, KeyCode::Gamepad16),
+    (Key::BTN_TRIGGER, KeyCode::Gamepad17),
+    (Key::BTN_THUMB, KeyCode::Gamepad18),
+    (Key::BTN_THUMB2, KeyCode::Gamepad19),
+];
+
+/// How often to look for newly connected devices.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(2);
+/// How long to wait for events before checking whether to stop.
+const POLL_TIMEOUT: Duration = Duration::from_millis(200);
+
+static LAST_PRESSED: Mutex<Option<KeyCode>> = Mutex::new(None);
+
+pub fn is_gamepad_button(key_code: KeyCode) -> bool {
+    BUTTONS.iter().any(|&(_, button)| button == key_code)
+}
+
+/// Takes the button that was pressed last, so it can be bound in the settings.
+pub fn take_last_pressed() -> Option<KeyCode> {
+    LAST_PRESSED.lock().unwrap().take()
+}
+
+fn button(key: Key) -> Option<KeyCode> {
+    BUTTONS
+        .iter()
+        .find(|&&(button_key, _)| button_key == key)
+        .map(|&(_, button)| button)
+}
+
+fn is_gamepad(device: &Device) -> bool {
+    device.supported_keys().map_or(false, |keys| {
+        keys.contains(Key::BTN_SOUTH) || keys.contains(Key::BTN_TRIGGER)
+    })
+}
+
+/// Reads the buttons of gamepads and joysticks through evdev in the
+/// background. The global hotkeys on Linux only see the keyboard, so the
+/// buttons are reported as the gamepad key codes instead. Devices that get
+/// connected later, including virtual uinput devices, are picked up as well.
+/// Dropping it stops all the threads and waits for them to finish.
+pub struct Gamepads {
+    stop: Arc<AtomicBool>,
+    scanner: Option<JoinHandle<()>>,
+}
+
+impl Gamepads {
+    pub fn spawn(on_press: impl Fn(KeyCode) + Send + Sync + 'static) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let scanner = thread::Builder::new()
+            .name("Gamepad Scanner".into())
+            .spawn({
+                let stop = stop.clone();
+                move || scan(Arc::new(on_press), &stop)
+            });
+
+        let scanner = match scanner {
+            Ok(scanner) => Some(scanner),
+            Err(e) => {
+                log::error!("Failed to start looking for gamepads: {e}");
+                None
+            }
+        };
+
+        Self { stop, scanner }
+    }
+}
+
+impl Drop for Gamepads {
+    fn drop(&mut self) {
+        self.stop.store(true, atomic::Ordering::Relaxed);
+        if let Some(scanner) = self.scanner.take() {
+            scanner.thread().unpark();
+            let _ = scanner.join();
+        }
+    }
+}
+
+fn scan(on_press: Arc<dyn Fn(KeyCode) + Send + Sync>, stop: &Arc<AtomicBool>) {
+    let open_devices = Arc::new(Mutex::new(HashSet::<PathBuf>::new()));
+    let mut readers = Vec::new();
+
+    while !stop.load(atomic::Ordering::Relaxed) {
+        readers.retain(|reader: &JoinHandle<()>| !reader.is_finished());
+
+        for (path, device) in evdev::enumerate() {
+            if !is_gamepad(&device) || !open_devices.lock().unwrap().insert(path.clone()) {
+                continue;
+            }
+            log::info!(
+                "Using the gamepad {} at {}",
+                device.name().unwrap_or("Unknown"),
+                path.display()
+            );
+
+            let on_press = on_press.clone();
+            let open_devices = open_devices.clone();
+            let stop = stop.clone();
+            let result = thread::Builder::new()
+                .name("Gamepad".into())
+                .spawn(move || {
+                    read_buttons(device, &*on_press, &stop);
+                    open_devices.lock().unwrap().remove(&path);
+                });
+            match result {
+                Ok(reader) => readers.push(reader),
+                Err(e) => log::error!("Failed to start reading the gamepad: {e}"),
+            }
+        }
+
+        // Dropping the gamepads wakes the thread up early.
+        thread::park_timeout(RESCAN_INTERVAL);
+    }
+
+    for reader in readers {
+        let _ = reader.join();
+    }
+}
+
+/// Waits for the device to have events, so the thread can stop in time even
+/// if no buttons are pressed.
+fn wait_for_events(device: &Device) -> io::Result<bool> {
+    let mut fd = libc::pollfd {
+        fd: device.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    match unsafe { libc::poll(&mut fd, 1, POLL_TIMEOUT.as_millis() as libc::c_int) } {
+        -1 => Err(io::Error::last_os_error()),
+        0 => Ok(false),
+        _ => Ok(true),
+    }
+}
+
+fn read_buttons(mut device: Device, on_press: &dyn Fn(KeyCode), stop: &AtomicBool) {
+    while !stop.load(atomic::Ordering::Relaxed) {
+        match wait_for_events(&device) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                log::error!("Failed waiting for the gamepad: {e}");
+                return;
+            }
+        }
+        let events = match device.fetch_events() {
+            Ok(events) => events,
+            Err(e) => {
+                // This usually means that the device got disconnected.
+                log::info!("Stopped reading the gamepad: {e}");
+                return;
+            }
+        };
+        for event in events {
+            // A value of 1 is a press, 0 a release and 2 a repeat.
+            if let (InputEventKind::Key(key), 1) = (event.kind(), event.value()) {
+                if let Some(button) = button(key) {
+                    *LAST_PRESSED.lock().unwrap() = Some(button);
+                    on_press(button);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use evdev::{uinput::VirtualDeviceBuilder, AttributeSet, EventType, InputEvent};
+
+    use super::*;
+
+    #[test]
+    fn buttons_have_distinct_key_codes() {
+        for (index, &(key, button)) in BUTTONS.iter().enumerate() {
+            assert!(BUTTONS[..index]
+                .iter()
+                .all(|&(other_key, other_button)| other_key != key && other_button != button));
+        }
+        assert_ne!(button(Key::BTN_SOUTH), button(Key::BTN_TRIGGER));
+        assert_eq!(button(Key::KEY_A), None);
+    }
+
+    #[test]
+    #[ignore = "needs write access to /dev/uinput"]
+    fn reads_virtual_gamepad() {
+        let mut keys = AttributeSet::<Key>::new();
+        keys.insert(Key::BTN_SOUTH);
+        keys.insert(Key::BTN_TRIGGER);
+        let mut device = VirtualDeviceBuilder::new()
+            .unwrap()
+            .name("LiveSplit One Test Gamepad")
+            .with_keys(&keys)
+            .unwrap()
+            .build()
+            .unwrap();
+        // Give udev some time to create the device node.
+        thread::sleep(Duration::from_millis(500));
+
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        let gamepads = Gamepads::spawn(move |button| {
+            let _ = sender.lock().unwrap().send(button);
+        });
+        thread::sleep(Duration::from_millis(500));
+
+        for key in [Key::BTN_SOUTH, Key::BTN_TRIGGER] {
+            device
+                .emit(&[InputEvent::new(EventType::KEY, key.code(), 1)])
+                .unwrap();
+            device
+                .emit(&[InputEvent::new(EventType::KEY, key.code(), 0)])
+                .unwrap();
+        }
+
+        let timeout = Duration::from_secs(2);
+        assert_eq!(receiver.recv_timeout(timeout), Ok(KeyCode::Gamepad0));
+        assert_eq!(receiver.recv_timeout(timeout), Ok(KeyCode::Gamepad17));
+
+        // Stopping has to finish even though the device is still connected.
+        drop(gamepads);
+    }
+}
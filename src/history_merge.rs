@@ -0,0 +1,415 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs,
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+use livesplit_core::{
+    run::{parser::composite, Attempt},
+    timing::formatter::{Regular, TimeFormatter},
+    Run, TimeSpan, TimingMethod,
+};
+
+use crate::timing::{fastest, TIMING_METHODS};
+
+/// What merging the attempt history of other splits changes.
+#[derive(Default)]
+pub struct Report {
+    pub added_attempts: usize,
+    pub duplicate_attempts: usize,
+    /// Attempts without a start time can't be told apart from the ones that
+    /// are already there, so they are left out.
+    pub skipped_attempts: usize,
+    pub improved_best_segments: Vec<String>,
+    /// The old and the new personal best, if the other splits have the better
+    /// one.
+    pub personal_best: Option<(Option<TimeSpan>, TimeSpan)>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Attempts added: {}", self.added_attempts)?;
+        writeln!(f, "Attempts already present: {}", self.duplicate_attempts)?;
+        if self.skipped_attempts != 0 {
+            writeln!(
+                f,
+                "Attempts skipped for lacking a start time: {}",
+                self.skipped_attempts
+            )?;
+        }
+
+        if self.improved_best_segments.is_empty() {
+            writeln!(f, "No best segments improved.")?;
+        } else {
+            writeln!(
+                f,
+                "Best segments improved: {}",
+                self.improved_best_segments.join(", ")
+            )?;
+        }
+
+        match self.personal_best {
+            Some((old, new)) => write!(
+                f,
+                "Personal best improved: {} → {}",
+                old.map_or_else(|| String::from("None"), format_time),
+                format_time(new),
+            ),
+            None => write!(f, "The personal best stays the same."),
+        }
+    }
+}
+
+fn format_time(time: TimeSpan) -> String {
+    Regular::new().format(time).to_string()
+}
+
+fn started(attempt: &Attempt) -> Option<i64> {
+    Some(attempt.started()?.time.unix_timestamp())
+}
+
+fn personal_best(run: &Run, timing_method: TimingMethod) -> Option<TimeSpan> {
+    run.segments().last()?.personal_best_split_time()[timing_method]
+}
+
+/// Loads the other splits and merges their attempt history into the run.
+pub fn merge_file(run: &Run, path: &Path, timing_method: TimingMethod) -> Result<(Run, Report)> {
+    let file = fs::read(path).context("Failed reading the file.")?;
+    let other = composite::parse(&file, Some(path))
+        .context("Failed parsing the file.")?
+        .run;
+    merge(run, &other, timing_method)
+}
+
+/// Combines the attempt histories of two copies of the same splits. The
+/// attempts of the other splits that started at a different time than all the
+/// attempts of the run are added after them, together with their segment
+/// times. The best segments are recomputed from the combined history, and the
+/// personal best of the other splits is taken over for the timing method if
+/// it's faster. The split times of the other timing method stay the same.
+pub fn merge(run: &Run, other: &Run, timing_method: TimingMethod) -> Result<(Run, Report)> {
+    if run.game_name() != other.game_name() || run.category_name() != other.category_name() {
+        bail!("The splits are for a different game or category.");
+    }
+    let same_segments = run.len() == other.len()
+        && run
+            .segments()
+            .iter()
+            .zip(other.segments())
+            .all(|(segment, other_segment)| segment.name() == other_segment.name());
+    if !same_segments {
+        bail!("The splits have different segments, so their histories can't be merged.");
+    }
+
+    let mut merged = run.clone();
+    let mut report = Report::default();
+
+    let mut known: BTreeSet<i64> = run.attempt_history().iter().filter_map(started).collect();
+    let mut new_attempts: Vec<&Attempt> = Vec::new();
+    for attempt in other.attempt_history() {
+        match started(attempt) {
+            Some(started) if known.insert(started) => new_attempts.push(attempt),
+            Some(_) => report.duplicate_attempts += 1,
+            None => report.skipped_attempts += 1,
+        }
+    }
+    new_attempts.sort_by_key(|attempt| started(attempt));
+
+    // The new attempts get numbered after all the attempts that the run
+    // already knows about, including the ones only left in segment histories.
+    let highest_index = run
+        .attempt_history()
+        .iter()
+        .map(|attempt| attempt.index())
+        .chain(
+            run.segments()
+                .iter()
+                .flat_map(|segment| segment.segment_history().iter().map(|&(id, _)| id)),
+        )
+        .max()
+        .unwrap_or_default()
+        .max(0);
+
+    let mut new_indices = BTreeMap::new();
+    for (index, attempt) in (highest_index + 1..).zip(new_attempts) {
+        new_indices.insert(attempt.index(), index);
+        merged.add_attempt_with_index(
+            attempt.time(),
+            index,
+            attempt.started(),
+            attempt.ended(),
+            attempt.pause_time(),
+        );
+    }
+    report.added_attempts = new_indices.len();
+    merged.set_attempt_count(run.attempt_count() + report.added_attempts as u32);
+
+    for (segment, other_segment) in merged.segments_mut().iter_mut().zip(other.segments()) {
+        for &(id, time) in other_segment.segment_history().iter() {
+            if let Some(&index) = new_indices.get(&id) {
+                segment.segment_history_mut().insert(index, time);
+            }
+        }
+
+        let mut improved = false;
+        for timing_method in TIMING_METHODS {
+            let old_best = segment.best_segment_time()[timing_method];
+            let history = segment
+                .segment_history()
+                .iter()
+                .filter_map(|(_, time)| time[timing_method]);
+            let best = fastest(
+                history
+                    .chain(old_best)
+                    .chain(other_segment.best_segment_time()[timing_method]),
+            );
+            if best.is_some() && old_best.map_or(true, |old_best| best < Some(old_best)) {
+                segment.best_segment_time_mut()[timing_method] = best;
+                improved = true;
+            }
+        }
+        if improved {
+            report
+                .improved_best_segments
+                .push(segment.name().to_owned());
+        }
+    }
+
+    let old_personal_best = personal_best(run, timing_method);
+    if let Some(other_personal_best) = personal_best(other, timing_method) {
+        if old_personal_best.map_or(true, |old| other_personal_best < old) {
+            for (segment, other_segment) in merged.segments_mut().iter_mut().zip(other.segments()) {
+                segment.personal_best_split_time_mut()[timing_method] =
+                    other_segment.personal_best_split_time()[timing_method];
+            }
+            report.personal_best = Some((old_personal_best, other_personal_best));
+        }
+    }
+
+    merged.mark_as_modified();
+    Ok((merged, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use livesplit_core::{AtomicDateTime, DateTime, Segment, Time};
+
+    use super::*;
+
+    fn seconds(seconds: f64) -> TimeSpan {
+        TimeSpan::from_seconds(seconds)
+    }
+
+    fn real_time(time: Option<f64>) -> Time {
+        Time::new().with_real_time(time.map(seconds))
+    }
+
+    fn new_run() -> Run {
+        let mut run = Run::new();
+        run.set_game_name("Game");
+        run.set_category_name("Any%");
+        run.push_segment(Segment::new("First"));
+        run.push_segment(Segment::new("Second"));
+        run
+    }
+
+    /// Adds an attempt that started at the Unix timestamp, along with its
+    /// segment times.
+    fn add_attempt(run: &mut Run, index: i32, started: Option<i64>, times: [Option<f64>; 2]) {
+        let started = started.map(|started| AtomicDateTime {
+            time: DateTime::from_unix_timestamp(started).unwrap(),
+            synced_with_atomic_clock: true,
+        });
+        run.add_attempt_with_index(Time::default(), index, started, None, None);
+        run.set_attempt_count(run.attempt_count() + 1);
+        for (segment, time) in run.segments_mut().iter_mut().zip(times) {
+            segment.segment_history_mut().insert(index, real_time(time));
+        }
+    }
+
+    fn attempts(run: &Run) -> Vec<(i32, Option<i64>)> {
+        run.attempt_history()
+            .iter()
+            .map(|attempt| (attempt.index(), started(attempt)))
+            .collect()
+    }
+
+    fn history(segment: &Segment) -> Vec<(i32, Option<TimeSpan>)> {
+        let mut history: Vec<_> = segment
+            .segment_history()
+            .iter()
+            .map(|&(id, time)| (id, time.real_time))
+            .collect();
+        history.sort_by_key(|&(id, _)| id);
+        history
+    }
+
+    #[test]
+    fn adds_new_attempts_after_existing_ones() {
+        let mut run = new_run();
+        add_attempt(&mut run, 1, Some(1000), [Some(30.0), Some(40.0)]);
+        add_attempt(&mut run, 2, Some(2000), [Some(31.0), None]);
+
+        let mut other = new_run();
+        add_attempt(&mut other, 1, Some(1000), [Some(30.0), Some(40.0)]);
+        add_attempt(&mut other, 2, Some(3000), [Some(32.0), Some(42.0)]);
+        add_attempt(&mut other, 3, None, [Some(20.0), Some(20.0)]);
+        add_attempt(&mut other, 4, Some(1500), [Some(33.0), None]);
+
+        let (merged, report) = merge(&run, &other, TimingMethod::RealTime).unwrap();
+
+        assert_eq!(report.added_attempts, 2);
+        assert_eq!(report.duplicate_attempts, 1);
+        assert_eq!(report.skipped_attempts, 1);
+        assert_eq!(merged.attempt_count(), 4);
+
+        // The new attempts are numbered in the order they were started in.
+        assert_eq!(
+            attempts(&merged),
+            [
+                (1, Some(1000)),
+                (2, Some(2000)),
+                (3, Some(1500)),
+                (4, Some(3000)),
+            ]
+        );
+        assert_eq!(
+            history(&merged.segments()[0]),
+            [
+                (1, Some(seconds(30.0))),
+                (2, Some(seconds(31.0))),
+                (3, Some(seconds(33.0))),
+                (4, Some(seconds(32.0))),
+            ]
+        );
+        assert_eq!(
+            history(&merged.segments()[1]),
+            [
+                (1, Some(seconds(40.0))),
+                (2, None),
+                (3, None),
+                (4, Some(seconds(42.0))),
+            ]
+        );
+    }
+
+    #[test]
+    fn numbers_after_attempts_only_left_in_segment_history() {
+        let mut run = new_run();
+        add_attempt(&mut run, 1, Some(1000), [Some(30.0), Some(40.0)]);
+        for segment in run.segments_mut() {
+            segment
+                .segment_history_mut()
+                .insert(5, real_time(Some(50.0)));
+        }
+
+        let mut other = new_run();
+        add_attempt(&mut other, 1, Some(2000), [Some(32.0), Some(42.0)]);
+
+        let (merged, _) = merge(&run, &other, TimingMethod::RealTime).unwrap();
+
+        assert_eq!(attempts(&merged), [(1, Some(1000)), (6, Some(2000))]);
+        assert_eq!(
+            history(&merged.segments()[1]),
+            [
+                (1, Some(seconds(40.0))),
+                (5, Some(seconds(50.0))),
+                (6, Some(seconds(42.0))),
+            ]
+        );
+    }
+
+    #[test]
+    fn improves_best_segments() {
+        let mut run = new_run();
+        run.segments_mut()[0].set_best_segment_time(real_time(Some(30.0)));
+        run.segments_mut()[1].set_best_segment_time(real_time(Some(40.0)));
+
+        let mut other = new_run();
+        other.segments_mut()[0].set_best_segment_time(real_time(Some(25.0)));
+        other.segments_mut()[1].set_best_segment_time(real_time(Some(45.0)));
+        add_attempt(&mut other, 1, Some(1000), [Some(28.0), Some(38.0)]);
+
+        let (merged, report) = merge(&run, &other, TimingMethod::RealTime).unwrap();
+
+        assert_eq!(report.improved_best_segments, ["First", "Second"]);
+        let segments = merged.segments();
+        assert_eq!(segments[0].best_segment_time(), real_time(Some(25.0)));
+        assert_eq!(segments[1].best_segment_time(), real_time(Some(38.0)));
+    }
+
+    #[test]
+    fn keeps_faster_best_segments() {
+        let mut run = new_run();
+        run.segments_mut()[0].set_best_segment_time(real_time(Some(30.0)));
+
+        let mut other = new_run();
+        add_attempt(&mut other, 1, Some(1000), [Some(35.0), None]);
+
+        let (merged, report) = merge(&run, &other, TimingMethod::RealTime).unwrap();
+
+        assert!(report.improved_best_segments.is_empty());
+        assert_eq!(
+            merged.segments()[0].best_segment_time(),
+            real_time(Some(30.0))
+        );
+        assert_eq!(merged.segments()[1].best_segment_time(), Time::default());
+    }
+
+    #[test]
+    fn takes_over_personal_best_for_the_timing_method() {
+        let split_times = |real_time: [f64; 2], game_time: [f64; 2]| {
+            [0, 1].map(|index| {
+                Time::new()
+                    .with_real_time(Some(seconds(real_time[index])))
+                    .with_game_time(Some(seconds(game_time[index])))
+            })
+        };
+
+        let mut run = new_run();
+        for (segment, time) in run
+            .segments_mut()
+            .iter_mut()
+            .zip(split_times([50.0, 100.0], [45.0, 95.0]))
+        {
+            segment.set_personal_best_split_time(time);
+        }
+
+        let mut other = new_run();
+        for (segment, time) in other
+            .segments_mut()
+            .iter_mut()
+            .zip(split_times([40.0, 90.0], [50.0, 99.0]))
+        {
+            segment.set_personal_best_split_time(time);
+        }
+
+        let (merged, report) = merge(&run, &other, TimingMethod::RealTime).unwrap();
+
+        assert_eq!(
+            report.personal_best,
+            Some((Some(seconds(100.0)), seconds(90.0)))
+        );
+        // The game times of the run stay, as they are faster.
+        let expected = split_times([40.0, 90.0], [45.0, 95.0]);
+        for (segment, expected) in merged.segments().iter().zip(expected) {
+            assert_eq!(segment.personal_best_split_time(), expected);
+        }
+
+        let (merged, report) = merge(&run, &other, TimingMethod::GameTime).unwrap();
+        assert!(report.personal_best.is_none());
+        assert_eq!(
+            merged.segments()[1].personal_best_split_time(),
+            run.segments()[1].personal_best_split_time()
+        );
+    }
+
+    #[test]
+    fn rejects_different_segments() {
+        let run = new_run();
+        let mut other = new_run();
+        other.segments_mut()[1].set_name("Other");
+        assert!(merge(&run, &other, TimingMethod::RealTime).is_err());
+    }
+}
@@ -8,13 +8,15 @@ use druid::{
 };
 use livesplit_core::hotkey::{KeyCode, Modifiers};
 
+#[cfg(all(feature = "gamepad", target_os = "linux"))]
+use crate::gamepad;
 use crate::{consts::GRID_BORDER, HOTKEY_SYSTEM};
 
 #[derive(Clone, Copy)]
 pub struct Hotkey(pub Option<livesplit_core::hotkey::Hotkey>);
 
 impl Hotkey {
-    fn new(key: &druid::KeyEvent) -> Self {
+    pub fn new(key: &druid::KeyEvent) -> Self {
         let key_code = match key.code {
             Code::Backquote => KeyCode::Backquote,
             Code::Backslash => KeyCode::Backslash,
@@ -283,6 +285,17 @@ impl Widget<Hotkey> for HotkeyButton {
         } else if let Event::MouseUp(_) = event {
             ctx.request_focus();
         }
+
+        #[cfg(all(feature = "gamepad", target_os = "linux"))]
+        if let Event::AnimFrame(_) = event {
+            if ctx.is_focused() {
+                if let Some(button) = gamepad::take_last_pressed() {
+                    *data = Hotkey(Some(button.with_modifiers(Modifiers::empty())));
+                }
+                ctx.request_anim_frame();
+            }
+        }
+
         self.0.event(ctx, event, data, env)
     }
 
@@ -290,6 +303,13 @@ impl Widget<Hotkey> for HotkeyButton {
         if let LifeCycle::BuildFocusChain = event {
             ctx.register_for_focus();
         } else if let LifeCycle::FocusChanged(_) = event {
+            // Gamepad buttons can't be received as key events, so they are
+            // polled while the button is focused.
+            #[cfg(all(feature = "gamepad", target_os = "linux"))]
+            if ctx.is_focused() {
+                gamepad::take_last_pressed();
+                ctx.request_anim_frame();
+            }
             ctx.request_paint();
         }
         self.0.lifecycle(ctx, event, data, env)
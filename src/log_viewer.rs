@@ -0,0 +1,264 @@
+use std::{collections::VecDeque, fmt, rc::Rc, sync::Mutex, time::Duration};
+
+use druid::{
+    lens::Identity,
+    theme,
+    widget::{
+        Button, CrossAxisAlignment, Flex, Label, LineBreaking, List, ListIter, Scroll, TextBox,
+    },
+    Application, Color, Data, Lens, LensExt, Widget, WidgetExt,
+};
+
+use crate::{
+    combo_box,
+    consts::{BUTTON_BORDER, BUTTON_SPACING, DIALOG_BUTTON_HEIGHT, GRID_BORDER, MARGIN},
+    refresh::Refresh,
+    striped_row::{Striped, StripedRow},
+};
+
+const MAX_LINES: usize = 1000;
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+const LEVELS: &[&str] = &["Error", "Warning", "Info", "Debug", "Trace"];
+
+struct RingBuffer {
+    lines: VecDeque<LogLine>,
+    generation: u64,
+}
+
+static RING_BUFFER: Mutex<RingBuffer> = Mutex::new(RingBuffer {
+    lines: VecDeque::new(),
+    generation: 0,
+});
+
+/// The level the log is captured at, independent of the level of the log
+/// file. The application's own records are all kept, while the ones of the
+/// libraries are limited, so they don't push everything else out.
+pub const CAPTURE_LEVEL: log::LevelFilter = log::LevelFilter::Info;
+pub const CAPTURE_LEVEL_OWN: log::LevelFilter = log::LevelFilter::Trace;
+
+/// Receives the log records at the capture levels, so the log can be viewed
+/// from within the application, even if it isn't written to a file.
+pub fn capture(record: &log::Record) {
+    let line = LogLine {
+        time: chrono::Local::now()
+            .format("[%Y-%m-%d %H:%M:%S]")
+            .to_string(),
+        level: record.level(),
+        target: record.target().to_owned(),
+        message: record.args().to_string(),
+    };
+
+    let mut buffer = RING_BUFFER.lock().unwrap();
+    if buffer.lines.len() == MAX_LINES {
+        buffer.lines.pop_front();
+    }
+    buffer.lines.push_back(line);
+    buffer.generation += 1;
+}
+
+#[derive(Clone)]
+struct LogLine {
+    time: String,
+    level: log::Level,
+    target: String,
+    message: String,
+}
+
+impl fmt::Display for LogLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}[{}][{}] {}",
+            self.time, self.target, self.level, self.message
+        )
+    }
+}
+
+#[derive(Clone, Data, Lens)]
+pub struct State {
+    lines: Rc<Vec<LogLine>>,
+    #[data(ignore)]
+    generation: u64,
+    level: usize,
+    target: String,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl State {
+    pub fn new() -> Self {
+        let mut state = Self {
+            lines: Rc::new(Vec::new()),
+            generation: u64::MAX,
+            level: 2,
+            target: String::new(),
+        };
+        state.refresh();
+        state
+    }
+
+    fn refresh(&mut self) {
+        let buffer = RING_BUFFER.lock().unwrap();
+        if buffer.generation != self.generation {
+            self.generation = buffer.generation;
+            self.lines = Rc::new(buffer.lines.iter().cloned().collect());
+        }
+    }
+
+    fn max_level(&self) -> log::Level {
+        match self.level {
+            0 => log::Level::Error,
+            1 => log::Level::Warn,
+            2 => log::Level::Info,
+            3 => log::Level::Debug,
+            _ => log::Level::Trace,
+        }
+    }
+
+    fn filtered(&self) -> impl Iterator<Item = &LogLine> {
+        let max_level = self.max_level();
+        let target = self.target.trim().to_lowercase();
+        self.lines.iter().filter(move |line| {
+            line.level <= max_level
+                && (target.is_empty() || line.target.to_lowercase().contains(&target))
+        })
+    }
+
+    fn filtered_text(&self) -> String {
+        use std::fmt::Write;
+        let mut text = String::new();
+        for line in self.filtered() {
+            let _ = writeln!(text, "{line}");
+        }
+        text
+    }
+}
+
+#[derive(Clone, Data)]
+struct Row {
+    index: usize,
+    time: String,
+    target: String,
+    message: String,
+    #[data(same_fn = "PartialEq::eq")]
+    level: log::Level,
+}
+
+impl ListIter<Row> for State {
+    fn for_each(&self, mut cb: impl FnMut(&Row, usize)) {
+        for (index, line) in self.filtered().enumerate() {
+            let row = Row {
+                index,
+                time: line.time.clone(),
+                target: line.target.clone(),
+                message: line.message.clone(),
+                level: line.level,
+            };
+            cb(&row, index);
+        }
+    }
+
+    fn for_each_mut(&mut self, mut cb: impl FnMut(&mut Row, usize)) {
+        self.for_each(|row, index| cb(&mut row.clone(), index))
+    }
+
+    fn data_len(&self) -> usize {
+        self.filtered().count()
+    }
+}
+
+fn level_color(level: log::Level) -> Option<Color> {
+    match level {
+        log::Level::Error => Some(Color::rgb8(0xff, 0x55, 0x55)),
+        log::Level::Warn => Some(Color::rgb8(0xff, 0xc8, 0x3d)),
+        log::Level::Info => None,
+        log::Level::Debug | log::Level::Trace => Some(Color::grey8(0x90)),
+    }
+}
+
+impl Striped for Row {
+    fn index(&self) -> usize {
+        self.index
+    }
+}
+
+fn row() -> impl Widget<Row> {
+    StripedRow::new(
+        Flex::row()
+            .cross_axis_alignment(CrossAxisAlignment::Start)
+            .with_spacer(6.0)
+            .with_child(Label::new(|row: &Row, _: &_| row.time.clone()).fix_width(170.0))
+            .with_spacer(GRID_BORDER)
+            .with_child(
+                Label::new(|row: &Row, _: &_| row.level.to_string())
+                    .env_scope(|env, row: &Row| {
+                        if let Some(color) = level_color(row.level) {
+                            env.set(theme::TEXT_COLOR, color);
+                        }
+                    })
+                    .fix_width(60.0),
+            )
+            .with_spacer(GRID_BORDER)
+            .with_child(
+                Label::new(|row: &Row, _: &_| row.target.clone())
+                    .with_line_break_mode(LineBreaking::Clip)
+                    .fix_width(140.0),
+            )
+            .with_spacer(GRID_BORDER)
+            .with_flex_child(
+                Label::new(|row: &Row, _: &_| row.message.clone())
+                    .with_line_break_mode(LineBreaking::WordWrap)
+                    .expand_width(),
+                1.0,
+            )
+            .with_spacer(6.0)
+            .padding(2.0),
+    )
+}
+
+fn filters() -> impl Widget<State> {
+    Flex::row()
+        .with_child(Label::new("Level"))
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            combo_box::static_list(LEVELS)
+                .lens(State::level)
+                .fix_width(120.0),
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_flex_child(
+            TextBox::new()
+                .with_placeholder("Filter by target")
+                .lens(State::target)
+                .expand_width(),
+            1.0,
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            Button::new("Copy to Clipboard")
+                .on_click(|_, state: &mut State, _| {
+                    Application::global()
+                        .clipboard()
+                        .put_string(state.filtered_text());
+                })
+                .fix_height(DIALOG_BUTTON_HEIGHT),
+        )
+}
+
+pub fn root_widget() -> impl Widget<State> {
+    Flex::column()
+        .with_child(filters())
+        .with_spacer(BUTTON_SPACING)
+        .with_flex_child(
+            Scroll::new(List::new(row).border(BUTTON_BORDER, 1.0).lens(Identity))
+                .vertical()
+                .expand_height(),
+            1.0,
+        )
+        .padding(MARGIN)
+        .controller(Refresh::new(REFRESH_INTERVAL, State::refresh))
+}
@@ -14,20 +14,44 @@ use once_cell::sync::Lazy;
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
-use crate::config::Config;
+use crate::{app_hotkeys::AppHotkeys, config::Config};
 
+mod app_hotkeys;
+mod atomic_write;
+#[cfg(feature = "auto-splitting")]
+mod auto_splitter_inspector;
+mod bulk_edit;
 mod color_button;
 mod combo_box;
 mod config;
 mod consts;
+mod export;
 mod formatter_scope;
+#[cfg(all(feature = "gamepad", target_os = "linux"))]
+mod gamepad;
+mod history_merge;
 mod hotkey_button;
 mod layout_editor;
+mod log_viewer;
 mod map_scope;
+mod notes;
+mod pasted_files;
+mod practice;
+mod progression;
+mod refresh;
 mod run_editor;
+mod safeguards;
+mod sections;
+mod segment_edit;
+mod segment_import;
 mod settings_editor;
 mod settings_table;
+mod splits_comparison;
+mod splits_format;
+mod splits_library;
+mod striped_row;
 mod timer_form;
+mod timing;
 
 mod software_renderer;
 // mod piet_renderer;
@@ -59,9 +83,18 @@ pub struct MainState {
     auto_splitter: Rc<livesplit_core::auto_splitting::Runtime>,
     #[data(ignore)]
     config: Rc<RefCell<Config>>,
+    #[data(ignore)]
+    app_hotkeys: Rc<RefCell<AppHotkeys>>,
     run_editor: Option<OpenWindow<run_editor::State>>,
     layout_editor: Option<OpenWindow<layout_editor::State>>,
     settings_editor: Option<OpenWindow<settings_editor::State>>,
+    log_viewer: Option<OpenWindow<log_viewer::State>>,
+    splits_library: Option<OpenWindow<splits_library::State>>,
+    splits_comparison: Option<OpenWindow<splits_comparison::State>>,
+    notes: Option<OpenWindow<notes::State>>,
+    progression: Option<OpenWindow<progression::State>>,
+    #[cfg(feature = "auto-splitting")]
+    auto_splitter_inspector: Option<OpenWindow<auto_splitter_inspector::State>>,
 }
 
 pub struct LayoutData {
@@ -94,7 +127,10 @@ impl MainState {
 
         let timer = timer.into_shared();
         let mut hotkey_system = HotkeySystem::new(timer.clone()).unwrap();
-        config.configure_hotkeys(&mut hotkey_system);
+        let mut app_hotkeys = AppHotkeys::new(timer.clone());
+        let failed_hotkeys = config.configure_hotkeys(&mut hotkey_system, &mut app_hotkeys);
+        app_hotkeys::warn_about_failures(&failed_hotkeys);
+        safeguards::configure(config.safeguards());
         *HOTKEY_SYSTEM.write().unwrap() = Some(hotkey_system);
 
         #[cfg(feature = "auto-splitting")]
@@ -112,9 +148,17 @@ impl MainState {
                 is_modified: false,
             })),
             config: Rc::new(RefCell::new(config)),
+            app_hotkeys: Rc::new(RefCell::new(app_hotkeys)),
             run_editor: None,
             layout_editor: None,
             settings_editor: None,
+            log_viewer: None,
+            splits_library: None,
+            splits_comparison: None,
+            notes: None,
+            progression: None,
+            #[cfg(feature = "auto-splitting")]
+            auto_splitter_inspector: None,
         }
     }
 }
@@ -163,6 +207,100 @@ impl Lens<MainState, settings_editor::State> for SettingsEditorLens {
     }
 }
 
+struct LogViewerLens;
+
+impl Lens<MainState, log_viewer::State> for LogViewerLens {
+    fn with<V, F: FnOnce(&log_viewer::State) -> V>(&self, data: &MainState, f: F) -> V {
+        f(&data.log_viewer.as_ref().unwrap().state)
+    }
+
+    fn with_mut<V, F: FnOnce(&mut log_viewer::State) -> V>(&self, data: &mut MainState, f: F) -> V {
+        f(&mut data.log_viewer.as_mut().unwrap().state)
+    }
+}
+
+struct SplitsLibraryLens;
+
+impl Lens<MainState, splits_library::State> for SplitsLibraryLens {
+    fn with<V, F: FnOnce(&splits_library::State) -> V>(&self, data: &MainState, f: F) -> V {
+        f(&data.splits_library.as_ref().unwrap().state)
+    }
+
+    fn with_mut<V, F: FnOnce(&mut splits_library::State) -> V>(
+        &self,
+        data: &mut MainState,
+        f: F,
+    ) -> V {
+        f(&mut data.splits_library.as_mut().unwrap().state)
+    }
+}
+
+struct NotesLens;
+
+impl Lens<MainState, notes::State> for NotesLens {
+    fn with<V, F: FnOnce(&notes::State) -> V>(&self, data: &MainState, f: F) -> V {
+        f(&data.notes.as_ref().unwrap().state)
+    }
+
+    fn with_mut<V, F: FnOnce(&mut notes::State) -> V>(&self, data: &mut MainState, f: F) -> V {
+        f(&mut data.notes.as_mut().unwrap().state)
+    }
+}
+
+struct SplitsComparisonLens;
+
+impl Lens<MainState, splits_comparison::State> for SplitsComparisonLens {
+    fn with<V, F: FnOnce(&splits_comparison::State) -> V>(&self, data: &MainState, f: F) -> V {
+        f(&data.splits_comparison.as_ref().unwrap().state)
+    }
+
+    fn with_mut<V, F: FnOnce(&mut splits_comparison::State) -> V>(
+        &self,
+        data: &mut MainState,
+        f: F,
+    ) -> V {
+        f(&mut data.splits_comparison.as_mut().unwrap().state)
+    }
+}
+
+struct ProgressionLens;
+
+impl Lens<MainState, progression::State> for ProgressionLens {
+    fn with<V, F: FnOnce(&progression::State) -> V>(&self, data: &MainState, f: F) -> V {
+        f(&data.progression.as_ref().unwrap().state)
+    }
+
+    fn with_mut<V, F: FnOnce(&mut progression::State) -> V>(
+        &self,
+        data: &mut MainState,
+        f: F,
+    ) -> V {
+        f(&mut data.progression.as_mut().unwrap().state)
+    }
+}
+
+#[cfg(feature = "auto-splitting")]
+struct AutoSplitterInspectorLens;
+
+#[cfg(feature = "auto-splitting")]
+impl Lens<MainState, auto_splitter_inspector::State> for AutoSplitterInspectorLens {
+    fn with<V, F: FnOnce(&auto_splitter_inspector::State) -> V>(
+        &self,
+        data: &MainState,
+        f: F,
+    ) -> V {
+        f(&data.auto_splitter_inspector.as_ref().unwrap().state)
+    }
+
+    fn with_mut<V, F: FnOnce(&mut auto_splitter_inspector::State) -> V>(
+        &self,
+        data: &mut MainState,
+        f: F,
+    ) -> V {
+        f(&mut data.auto_splitter_inspector.as_mut().unwrap().state)
+    }
+}
+
 fn main() {
     let config = Config::load();
     let window = config.build_window();
@@ -0,0 +1,214 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use druid::{
+    widget::{Button, CrossAxisAlignment, Flex, Label, LineBreaking, Scroll},
+    Data, FontDescriptor, FontFamily, FontWeight, Lens, Widget, WidgetExt,
+};
+use livesplit_core::SharedTimer;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    atomic_write,
+    config::Config,
+    consts::{BUTTON_BORDER, BUTTON_SPACING, MARGIN},
+    refresh::Refresh,
+};
+
+const REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+const SEGMENT_FONT: FontDescriptor = FontDescriptor::new(FontFamily::SYSTEM_UI)
+    .with_weight(FontWeight::BOLD)
+    .with_size(18.0);
+const NOTES_FONT: FontDescriptor = FontDescriptor::new(FontFamily::SYSTEM_UI).with_size(16.0);
+
+/// The route notes of the segments, keyed by the segment names. They are
+/// stored in a file next to the splits, so they don't get lost when the
+/// splits are saved in a format that doesn't know about them. Segments with
+/// the same name share their notes.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Notes {
+    notes: BTreeMap<String, String>,
+    /// The file the notes were loaded from or last saved to. Only that file
+    /// gets removed once all the notes are gone.
+    #[serde(skip)]
+    file: Option<PathBuf>,
+}
+
+impl Notes {
+    pub fn load(splits_path: &Path) -> Self {
+        let path = sidecar_path(splits_path);
+        let buf = match fs::read(&path) {
+            Ok(buf) => buf,
+            Err(e) => {
+                if e.kind() != io::ErrorKind::NotFound {
+                    log::error!("Failed to read the notes {}: {e}", path.display());
+                }
+                return Self::default();
+            }
+        };
+        match serde_yaml::from_slice::<Self>(&buf) {
+            Ok(notes) => Self {
+                file: Some(path),
+                ..notes
+            },
+            Err(e) => {
+                // Without a file to remove, the notes that couldn't be
+                // parsed don't get deleted when saving empty notes.
+                log::error!("Failed to parse the notes {}: {e}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    /// Writes the notes next to the splits. Without any notes, no file is
+    /// created, and the file is only removed if the notes came from it.
+    pub fn save(&mut self, splits_path: &Path) -> Result<()> {
+        let path = sidecar_path(splits_path);
+        if self.notes.is_empty() {
+            if self.file.as_deref() == Some(&*path) && path.exists() {
+                fs::remove_file(&path).context("Failed removing the notes.")?;
+            }
+            self.file = None;
+            return Ok(());
+        }
+        let buf = serde_yaml::to_string(self).context("Failed saving the notes.")?;
+        atomic_write::write(&path, buf).context("Failed writing the notes.")?;
+        self.file = Some(path);
+        Ok(())
+    }
+
+    pub fn get(&self, segment_name: &str) -> &str {
+        self.notes.get(segment_name).map_or("", String::as_str)
+    }
+
+    pub fn set(&mut self, segment_name: &str, notes: String) {
+        if notes.trim().is_empty() {
+            self.notes.remove(segment_name);
+        } else {
+            self.notes.insert(segment_name.to_owned(), notes);
+        }
+    }
+
+    /// Moves the notes over to the new name of a segment, unless there already
+    /// are notes for that name.
+    pub fn rename(&mut self, old_name: &str, new_name: &str) {
+        if self.notes.contains_key(new_name) {
+            return;
+        }
+        if let Some(notes) = self.notes.remove(old_name) {
+            self.notes.insert(new_name.to_owned(), notes);
+        }
+    }
+}
+
+/// `Splits.lss` keeps its notes in `Splits.notes.yml`.
+fn sidecar_path(splits_path: &Path) -> PathBuf {
+    splits_path.with_extension("notes.yml")
+}
+
+#[derive(Clone, Data, Lens)]
+pub struct State {
+    #[data(ignore)]
+    timer: SharedTimer,
+    #[data(ignore)]
+    config: Rc<RefCell<Config>>,
+    #[data(ignore)]
+    current_split: Option<usize>,
+    /// How many segments past the current one the notes are shown for.
+    ahead: isize,
+    segment: String,
+    notes: String,
+}
+
+impl State {
+    pub fn new(timer: SharedTimer, config: Rc<RefCell<Config>>) -> Self {
+        let mut state = Self {
+            timer,
+            config,
+            current_split: None,
+            ahead: 0,
+            segment: String::new(),
+            notes: String::new(),
+        };
+        state.refresh();
+        state
+    }
+
+    /// Follows the current split. Looking ahead is undone once the timer
+    /// moves on to another split.
+    fn refresh(&mut self) {
+        let timer = self.timer.read().unwrap();
+        let current_split = timer.current_split_index();
+        if current_split != self.current_split {
+            self.current_split = current_split;
+            self.ahead = 0;
+        }
+
+        let segments = timer.run().segments();
+        let current = current_split.unwrap_or_default().min(segments.len() - 1);
+        let index = (current as isize + self.ahead).clamp(0, segments.len() as isize - 1);
+        self.ahead = index - current as isize;
+
+        let name = segments[index as usize].name();
+        let notes = self.config.borrow().notes().get(name).to_owned();
+        let segment = match self.ahead {
+            0 => name.to_owned(),
+            ahead => format!("{name} ({ahead:+})"),
+        };
+        if segment != self.segment {
+            self.segment = segment;
+        }
+        if notes != self.notes {
+            self.notes = notes;
+        }
+    }
+
+    /// Shows the notes of the segments ahead of or behind the current split.
+    pub fn scroll(&mut self, segments: isize) {
+        self.ahead += segments;
+        self.refresh();
+    }
+}
+
+pub fn root_widget() -> impl Widget<State> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(
+            Flex::row()
+                .with_flex_child(
+                    Label::new(|state: &State, _: &_| state.segment.clone())
+                        .with_font(SEGMENT_FONT)
+                        .with_line_break_mode(LineBreaking::Clip)
+                        .expand_width(),
+                    1.0,
+                )
+                .with_spacer(BUTTON_SPACING)
+                .with_child(Button::new("◀").on_click(|_, state: &mut State, _| state.scroll(-1)))
+                .with_spacer(BUTTON_SPACING)
+                .with_child(Button::new("▶").on_click(|_, state: &mut State, _| state.scroll(1))),
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_flex_child(
+            Scroll::new(
+                Label::new(|state: &State, _: &_| state.notes.clone())
+                    .with_font(NOTES_FONT)
+                    .with_line_break_mode(LineBreaking::WordWrap)
+                    .expand_width()
+                    .padding(BUTTON_SPACING),
+            )
+            .vertical()
+            .expand()
+            .border(BUTTON_BORDER, 1.0),
+            1.0,
+        )
+        .padding(MARGIN)
+        .controller(Refresh::new(REFRESH_INTERVAL, State::refresh))
+}
@@ -0,0 +1,176 @@
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+/// How much of a file is looked at to tell splits and layouts apart.
+const PROBE_LEN: u64 = 4096;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FileKind {
+    Splits,
+    Layout,
+}
+
+/// Figures out whether a file that is meant to be opened without having gone
+/// through one of the file dialogs contains splits or a layout. Only the start
+/// of the file is looked at, as it gets parsed once it's opened anyway. That's
+/// also when the errors of files that are neither are shown.
+pub fn detect_file_kind(path: &Path) -> Result<FileKind> {
+    let mut start = Vec::new();
+    File::open(path)
+        .and_then(|file| file.take(PROBE_LEN).read_to_end(&mut start))
+        .context("Failed reading the file.")?;
+    Ok(file_kind(&start))
+}
+
+/// Layouts are either LiveSplit One's JSON layouts or the XML layouts of the
+/// original LiveSplit. Everything else is left to the splits parsers, which
+/// handle lots of formats.
+fn file_kind(start: &[u8]) -> FileKind {
+    let text = String::from_utf8_lossy(start);
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+
+    if text.starts_with('{') && text.contains("\"components\"") {
+        return FileKind::Layout;
+    }
+
+    if xml_root_element(text) == Some("Layout") {
+        return FileKind::Layout;
+    }
+
+    FileKind::Splits
+}
+
+/// The name of the first element, skipping the XML declaration, processing
+/// instructions and comments.
+fn xml_root_element(mut text: &str) -> Option<&str> {
+    loop {
+        text = text.trim_start().strip_prefix('<')?;
+        if let Some(rest) = text.strip_prefix('?') {
+            text = &rest[rest.find("?>")? + 2..];
+        } else if let Some(rest) = text.strip_prefix("!--") {
+            text = &rest[rest.find("-->")? + 3..];
+        } else {
+            let end = text
+                .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                .unwrap_or(text.len());
+            return Some(&text[..end]);
+        }
+    }
+}
+
+/// Extracts the paths of the files from text that was pasted. File managers
+/// usually provide them either as plain paths or as file:// URIs, one per
+/// line.
+pub fn paths_from_text(text: &str) -> Vec<PathBuf> {
+    text.lines()
+        .map(|line| line.trim().trim_matches('"'))
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.strip_prefix("file://") {
+            Some(uri) => {
+                let path = percent_decode(uri.strip_prefix("localhost").unwrap_or(uri));
+                // URIs of Windows paths look like file:///C:/...
+                if cfg!(windows) {
+                    PathBuf::from(path.strip_prefix('/').unwrap_or(&path))
+                } else {
+                    PathBuf::from(path)
+                }
+            }
+            None => PathBuf::from(line),
+        })
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+fn percent_decode(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        if byte == b'%' {
+            if let Some(decoded) = rest
+                .get(..2)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                bytes.push(decoded);
+                rest = &rest[2..];
+                continue;
+            }
+        }
+        bytes.push(byte);
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn detects_layouts() {
+        assert_eq!(
+            file_kind(br#"{"components":[{"Title":{}}],"general":{}}"#),
+            FileKind::Layout
+        );
+        assert_eq!(
+            file_kind(
+                "\u{feff}<?xml version=\"1.0\"?>\n<!-- <Run> -->\n<Layout version=\"1.6.1\">"
+                    .as_bytes()
+            ),
+            FileKind::Layout
+        );
+    }
+
+    #[test]
+    fn leaves_everything_else_to_the_splits() {
+        assert_eq!(
+            file_kind(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?><Run version=\"1.7.0\">"),
+            FileKind::Splits
+        );
+        assert_eq!(
+            file_kind(br#"{"_schema":"v1.0.1 https://splits.io/schema","segments":[]}"#),
+            FileKind::Splits
+        );
+        assert_eq!(file_kind(b"Title=Any%\nGoal=\n"), FileKind::Splits);
+        assert_eq!(file_kind(b""), FileKind::Splits);
+    }
+
+    #[test]
+    fn decodes_percent_escapes() {
+        assert_eq!(percent_decode("My%20Splits.lss"), "My Splits.lss");
+        assert_eq!(percent_decode("%E2%9C%93"), "\u{2713}");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn finds_pasted_paths() {
+        let dir =
+            std::env::temp_dir().join(format!("livesplit-one-pasted-files-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let first = dir.join("My Splits.lss");
+        let second = dir.join("Layout.ls1l");
+        fs::write(&first, "").unwrap();
+        fs::write(&second, "").unwrap();
+
+        let text = format!(
+            "# copied files\r\nfile://{}\r\n\"{}\"\r\nfile://{}\r\n{}\r\n",
+            first.display().to_string().replace(' ', "%20"),
+            second.display(),
+            dir.join("Missing.lss").display(),
+            dir.display(),
+        );
+        assert_eq!(paths_from_text(&text), [first, second]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,238 @@
+use livesplit_core::{Run, TimeSpan, Timer};
+
+use crate::timing::TIMING_METHODS;
+
+/// Practicing a section of the run. The timer gets a copy of the run that
+/// starts at the chosen segment, so attempts, personal bests and the attempt
+/// history only ever end up in that copy. The original run is put back once
+/// the practice is stopped.
+pub struct Practice {
+    original: Run,
+    start: usize,
+}
+
+impl Practice {
+    pub fn start(timer: &mut Timer, start: usize) -> Option<Self> {
+        let original = timer.run().clone();
+        if start >= original.len() {
+            return None;
+        }
+        timer.set_run(practice_run(&original, start)).ok()?;
+        Some(Self { original, start })
+    }
+
+    /// Restores the original run. If the golds are recorded, the best segments
+    /// of the practice are taken over where they are faster.
+    pub fn stop(self, timer: &mut Timer, record_golds: bool) {
+        timer.reset(record_golds);
+
+        let mut original = self.original;
+        if record_golds && take_golds(&mut original, timer.run(), self.start) {
+            original.regenerate_comparisons();
+            original.mark_as_modified();
+        }
+
+        // The original run has segments, so this can't fail.
+        let _ = timer.set_run(original);
+    }
+}
+
+/// Creates a copy of the run without the segments before the start segment.
+/// The split times of the comparisons are measured from the start segment on.
+/// If the split before it was skipped in a comparison, they are measured from
+/// the split before that, so the comparison isn't lost.
+fn practice_run(run: &Run, start: usize) -> Run {
+    let mut practice = run.clone();
+    practice.set_category_name(format!(
+        "{} (Practice from {})",
+        run.category_name(),
+        run.segment(start).name()
+    ));
+
+    if start != 0 {
+        let comparisons = run.custom_comparisons().to_vec();
+        practice.segments_mut().drain(..start);
+        for comparison in &comparisons {
+            for timing_method in TIMING_METHODS {
+                let start_time = run.segments()[..start]
+                    .iter()
+                    .rev()
+                    .find_map(|segment| segment.comparison(comparison)[timing_method])
+                    .unwrap_or_else(TimeSpan::zero);
+                for segment in practice.segments_mut() {
+                    let time = &mut segment.comparison_mut(comparison)[timing_method];
+                    *time = time.map(|time| time - start_time);
+                }
+            }
+        }
+    }
+
+    practice.clear_history();
+    practice.regenerate_comparisons();
+    practice
+}
+
+fn take_golds(original: &mut Run, practice: &Run, start: usize) -> bool {
+    let mut changed = false;
+    for (segment, practiced) in original.segments_mut()[start..]
+        .iter_mut()
+        .zip(practice.segments())
+    {
+        for timing_method in TIMING_METHODS {
+            let practiced = practiced.best_segment_time()[timing_method];
+            let best = &mut segment.best_segment_time_mut()[timing_method];
+            if practiced.map_or(false, |practiced| {
+                best.map_or(true, |best| practiced < best)
+            }) {
+                *best = practiced;
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use livesplit_core::{Segment, Time, TimingMethod};
+
+    use super::*;
+
+    fn seconds(seconds: f64) -> Option<TimeSpan> {
+        Some(TimeSpan::from_seconds(seconds))
+    }
+
+    fn time(real_time: Option<f64>, game_time: Option<f64>) -> Time {
+        Time::new()
+            .with_real_time(real_time.map(TimeSpan::from_seconds))
+            .with_game_time(game_time.map(TimeSpan::from_seconds))
+    }
+
+    /// Four segments of 10 seconds of real time and 8 seconds of game time
+    /// each, with a best segment of 9 seconds of real time.
+    fn run() -> Run {
+        let mut run = Run::new();
+        run.set_category_name("Any%");
+        for (index, name) in ["A", "B", "C", "D"].into_iter().enumerate() {
+            let split = (index + 1) as f64;
+            let mut segment = Segment::new(name);
+            segment.set_personal_best_split_time(time(Some(10.0 * split), Some(8.0 * split)));
+            segment.set_best_segment_time(time(Some(9.0), None));
+            run.push_segment(segment);
+        }
+        run.add_attempt_with_index(time(Some(40.0), Some(32.0)), 1, None, None, None);
+        run
+    }
+
+    fn personal_best(run: &Run, timing_method: TimingMethod) -> Vec<Option<TimeSpan>> {
+        run.segments()
+            .iter()
+            .map(|segment| segment.personal_best_split_time()[timing_method])
+            .collect()
+    }
+
+    #[test]
+    fn starts_at_the_chosen_segment() {
+        let practice = practice_run(&run(), 2);
+
+        assert_eq!(practice.category_name(), "Any% (Practice from C)");
+        assert_eq!(practice.len(), 2);
+        assert_eq!(practice.segment(0).name(), "C");
+        assert_eq!(
+            personal_best(&practice, TimingMethod::RealTime),
+            [seconds(10.0), seconds(20.0)]
+        );
+        assert_eq!(
+            personal_best(&practice, TimingMethod::GameTime),
+            [seconds(8.0), seconds(16.0)]
+        );
+        assert!(practice.attempt_history().is_empty());
+    }
+
+    #[test]
+    fn starts_at_the_first_or_the_last_segment() {
+        let run = run();
+
+        let practice = practice_run(&run, 0);
+        assert_eq!(practice.len(), 4);
+        assert_eq!(
+            personal_best(&practice, TimingMethod::RealTime),
+            personal_best(&run, TimingMethod::RealTime)
+        );
+        assert!(practice.attempt_history().is_empty());
+
+        let practice = practice_run(&run, 3);
+        assert_eq!(practice.len(), 1);
+        assert_eq!(
+            personal_best(&practice, TimingMethod::RealTime),
+            [seconds(10.0)]
+        );
+    }
+
+    #[test]
+    fn starts_after_a_skipped_split() {
+        let mut run = run();
+        run.segments_mut()[1]
+            .personal_best_split_time_mut()
+            .real_time = None;
+        run.segments_mut()[0]
+            .personal_best_split_time_mut()
+            .game_time = None;
+        run.segments_mut()[1]
+            .personal_best_split_time_mut()
+            .game_time = None;
+
+        let practice = practice_run(&run, 2);
+
+        // The real time counts from A, and the game time from the start.
+        assert_eq!(
+            personal_best(&practice, TimingMethod::RealTime),
+            [seconds(20.0), seconds(30.0)]
+        );
+        assert_eq!(
+            personal_best(&practice, TimingMethod::GameTime),
+            [seconds(24.0), seconds(32.0)]
+        );
+    }
+
+    #[test]
+    fn takes_faster_golds() {
+        let mut original = run();
+        let mut practice = practice_run(&original, 2);
+        *practice.segments_mut()[0].best_segment_time_mut() = time(Some(8.0), Some(7.0));
+        // The segment after a skipped split keeps the best segment it had.
+        *practice.segments_mut()[1].best_segment_time_mut() = time(Some(9.0), None);
+
+        assert!(take_golds(&mut original, &practice, 2));
+
+        let best_segments: Vec<_> = original
+            .segments()
+            .iter()
+            .map(|segment| segment.best_segment_time())
+            .map(|time| (time.real_time, time.game_time))
+            .collect();
+        assert_eq!(
+            best_segments,
+            [
+                (seconds(9.0), None),
+                (seconds(9.0), None),
+                (seconds(8.0), seconds(7.0)),
+                (seconds(9.0), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_golds_that_are_not_beaten() {
+        let mut original = run();
+        let mut practice = practice_run(&original, 0);
+        assert!(!take_golds(&mut original, &practice, 0));
+
+        *practice.segments_mut()[3].best_segment_time_mut() = time(Some(9.5), None);
+        assert!(!take_golds(&mut original, &practice, 0));
+        assert_eq!(
+            original.segment(3).best_segment_time().real_time,
+            seconds(9.0)
+        );
+    }
+}
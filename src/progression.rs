@@ -0,0 +1,517 @@
+use std::{collections::VecDeque, rc::Rc, time::Duration};
+
+use druid::{
+    kurbo::{BezPath, Circle, Line},
+    piet::{PietTextLayout, Text, TextLayout, TextLayoutBuilder},
+    widget::{CrossAxisAlignment, Flex, Label},
+    BoxConstraints, Color, Data, Env, Event, EventCtx, FontFamily, LayoutCtx, Lens, LifeCycle,
+    LifeCycleCtx, PaintCtx, Point, Rect, RenderContext, Size, UpdateCtx, Widget, WidgetExt,
+};
+use livesplit_core::{
+    timing::formatter::{Regular, TimeFormatter},
+    Run, SharedTimer, TimeSpan, TimingMethod,
+};
+
+use crate::{
+    combo_box,
+    consts::{BUTTON_BORDER, BUTTON_SPACING, MARGIN},
+    refresh::Refresh,
+    timing::{timing_method_index, TIMING_METHODS, TIMING_METHOD_NAMES},
+};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+const X_AXES: &[&str] = &["Attempt", "Date"];
+/// The number of finished attempts the rolling average is taken over.
+const ROLLING_ATTEMPTS: usize = 10;
+
+const LEFT_AXIS_WIDTH: f64 = 80.0;
+const BOTTOM_AXIS_HEIGHT: f64 = 26.0;
+const PADDING: f64 = 10.0;
+const Y_GRID_LINES: usize = 5;
+const FONT_SIZE: f64 = 12.0;
+
+const BACKGROUND_COLOR: Color = Color::grey8(0x12);
+const GRID_COLOR: Color = Color::grey8(0x2a);
+const TEXT_COLOR: Color = Color::grey8(0xb0);
+const ATTEMPT_COLOR: Color = Color::grey8(0x80);
+const PERSONAL_BEST_COLOR: Color = Color::rgb8(0xd8, 0xaf, 0x1f);
+const ROLLING_AVERAGE_COLOR: Color = Color::rgb8(0x3f, 0x8c, 0xff);
+const SUM_OF_BEST_COLOR: Color = Color::rgb8(0x4c, 0xd9, 0x64);
+
+/// A finished attempt, with the times in seconds.
+struct Attempt {
+    index: i32,
+    started: Option<i64>,
+    time: f64,
+    is_personal_best: bool,
+    rolling_average: f64,
+    sum_of_best: Option<f64>,
+}
+
+#[derive(Clone, Data, Lens)]
+pub struct State {
+    #[data(ignore)]
+    timer: SharedTimer,
+    #[data(ignore)]
+    history_len: Option<(usize, u32)>,
+    /// The finished attempts for real time and game time.
+    attempts: Rc<[Vec<Attempt>; 2]>,
+    timing_method: usize,
+    x_axis: usize,
+}
+
+impl State {
+    pub fn new(timer: SharedTimer) -> Self {
+        let timing_method = timing_method_index(timer.read().unwrap().current_timing_method());
+        let mut state = Self {
+            timer,
+            history_len: None,
+            attempts: Rc::new([Vec::new(), Vec::new()]),
+            timing_method,
+            x_axis: 0,
+        };
+        state.refresh();
+        state
+    }
+
+    /// Picks up the attempts that were finished since the chart was opened.
+    fn refresh(&mut self) {
+        let timer = self.timer.read().unwrap();
+        let run = timer.run();
+        let history_len = Some((run.attempt_history().len(), run.attempt_count()));
+        if history_len != self.history_len {
+            self.history_len = history_len;
+            self.attempts =
+                Rc::new(TIMING_METHODS.map(|timing_method| attempts(run, timing_method)));
+        }
+    }
+
+    fn by_date(&self) -> bool {
+        self.x_axis == 1
+    }
+
+    /// The attempts that can be placed on the x axis.
+    fn plotted(&self) -> impl Iterator<Item = (f64, &Attempt)> {
+        let by_date = self.by_date();
+        self.attempts[self.timing_method]
+            .iter()
+            .filter_map(move |attempt| {
+                let x = if by_date {
+                    attempt.started? as f64
+                } else {
+                    attempt.index as f64
+                };
+                Some((x, attempt))
+            })
+    }
+}
+
+fn attempts(run: &Run, timing_method: TimingMethod) -> Vec<Attempt> {
+    let mut attempts = Vec::new();
+    let mut personal_best = None;
+    let mut recent = VecDeque::with_capacity(ROLLING_ATTEMPTS);
+    let mut sum_of_best = SumOfBest::new(run, timing_method);
+
+    for attempt in run.attempt_history() {
+        let time = match attempt.time()[timing_method] {
+            Some(time) => time.total_seconds(),
+            None => continue,
+        };
+        let is_personal_best = personal_best.map_or(true, |best| time < best);
+        if is_personal_best {
+            personal_best = Some(time);
+        }
+        if recent.len() == ROLLING_ATTEMPTS {
+            recent.pop_front();
+        }
+        recent.push_back(time);
+
+        attempts.push(Attempt {
+            index: attempt.index(),
+            started: attempt
+                .started()
+                .map(|started| started.time.unix_timestamp()),
+            time,
+            is_personal_best,
+            rolling_average: recent.iter().sum::<f64>() / recent.len() as f64,
+            sum_of_best: sum_of_best.at(attempt.index()),
+        });
+    }
+
+    attempts
+}
+
+/// The sums of the best segments known at the end of the attempts. The best
+/// segments are built up while walking through the attempts in the order of
+/// their ids, which is the order of the attempt history. Times from before the
+/// attempt history, which have ids of zero or below, count from the start.
+struct SumOfBest {
+    /// The segment times of all the segments, ordered by their attempt.
+    times: Vec<(i32, usize, TimeSpan)>,
+    next: usize,
+    best_segments: Vec<Option<TimeSpan>>,
+}
+
+impl SumOfBest {
+    fn new(run: &Run, timing_method: TimingMethod) -> Self {
+        let mut times: Vec<_> = run
+            .segments()
+            .iter()
+            .enumerate()
+            .flat_map(|(index, segment)| {
+                segment
+                    .segment_history()
+                    .iter()
+                    .filter_map(move |&(id, time)| Some((id, index, time[timing_method]?)))
+            })
+            .collect();
+        times.sort_by_key(|&(id, ..)| id);
+
+        Self {
+            times,
+            next: 0,
+            best_segments: vec![None; run.len()],
+        }
+    }
+
+    fn at(&mut self, attempt: i32) -> Option<f64> {
+        while let Some(&(id, segment, time)) = self.times.get(self.next) {
+            if id > attempt {
+                break;
+            }
+            let best = &mut self.best_segments[segment];
+            if best.map_or(true, |best| time < best) {
+                *best = Some(time);
+            }
+            self.next += 1;
+        }
+
+        self.best_segments
+            .iter()
+            .map(|best| best.map(TimeSpan::total_seconds))
+            .sum()
+    }
+}
+
+fn text(ctx: &mut PaintCtx, text: String, color: Color) -> Option<PietTextLayout> {
+    ctx.text()
+        .new_text_layout(text)
+        .font(FontFamily::SYSTEM_UI, FONT_SIZE)
+        .text_color(color)
+        .build()
+        .ok()
+}
+
+fn format_time(seconds: f64) -> String {
+    Regular::new()
+        .format(TimeSpan::from_seconds(seconds))
+        .to_string()
+}
+
+fn format_x(x: f64, by_date: bool) -> String {
+    if by_date {
+        chrono::NaiveDateTime::from_timestamp_opt(x as i64, 0)
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    } else {
+        format!("#{}", x as i64)
+    }
+}
+
+/// Maps the attempts into the plot area.
+struct Scale {
+    plot: Rect,
+    x: (f64, f64),
+    y: (f64, f64),
+}
+
+impl Scale {
+    fn point(&self, x: f64, y: f64) -> Point {
+        let fx = if self.x.1 > self.x.0 {
+            (x - self.x.0) / (self.x.1 - self.x.0)
+        } else {
+            0.5
+        };
+        let fy = (y - self.y.0) / (self.y.1 - self.y.0);
+        Point::new(
+            self.plot.x0 + fx * self.plot.width(),
+            self.plot.y1 - fy * self.plot.height(),
+        )
+    }
+}
+
+struct Chart;
+
+impl Widget<State> for Chart {
+    fn event(&mut self, _: &mut EventCtx, _: &Event, _: &mut State, _: &Env) {}
+
+    fn lifecycle(&mut self, _: &mut LifeCycleCtx, _: &LifeCycle, _: &State, _: &Env) {}
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &State, data: &State, _: &Env) {
+        if !old_data.same(data) {
+            ctx.request_paint();
+        }
+    }
+
+    fn layout(&mut self, _: &mut LayoutCtx, bc: &BoxConstraints, _: &State, _: &Env) -> Size {
+        bc.max()
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &State, _: &Env) {
+        let size = ctx.size();
+        ctx.fill(size.to_rect(), &BACKGROUND_COLOR);
+
+        let plot = Rect::new(
+            LEFT_AXIS_WIDTH,
+            PADDING,
+            size.width - PADDING,
+            size.height - BOTTOM_AXIS_HEIGHT,
+        );
+
+        let (mut min_x, mut max_x) = (f64::INFINITY, f64::NEG_INFINITY);
+        let (mut min_y, mut max_y) = (f64::INFINITY, f64::NEG_INFINITY);
+        for (x, attempt) in data.plotted() {
+            min_x = min_x.min(x);
+            max_x = max_x.max(x);
+            for y in [Some(attempt.time), attempt.sum_of_best]
+                .into_iter()
+                .flatten()
+            {
+                min_y = min_y.min(y);
+                max_y = max_y.max(y);
+            }
+        }
+
+        if min_x > max_x || plot.width() <= 0.0 || plot.height() <= 0.0 {
+            if let Some(layout) = text(ctx, String::from("No finished attempts"), TEXT_COLOR) {
+                let text_size = layout.size();
+                ctx.draw_text(
+                    &layout,
+                    ((size.to_vec2() - text_size.to_vec2()) / 2.0).to_point(),
+                );
+            }
+            return;
+        }
+
+        let margin = ((max_y - min_y) * 0.05).max(1.0);
+        let scale = Scale {
+            plot,
+            x: (min_x, max_x),
+            y: ((min_y - margin).max(0.0), max_y + margin),
+        };
+
+        for i in 0..Y_GRID_LINES {
+            let y = scale.y.0 + (scale.y.1 - scale.y.0) * i as f64 / (Y_GRID_LINES - 1) as f64;
+            let pos = scale.point(min_x, y);
+            ctx.stroke(
+                Line::new((plot.x0, pos.y), (plot.x1, pos.y)),
+                &GRID_COLOR,
+                1.0,
+            );
+            if let Some(layout) = text(ctx, format_time(y), TEXT_COLOR) {
+                let text_size = layout.size();
+                ctx.draw_text(
+                    &layout,
+                    (
+                        plot.x0 - text_size.width - BUTTON_SPACING,
+                        pos.y - text_size.height / 2.0,
+                    ),
+                );
+            }
+        }
+
+        let by_date = data.by_date();
+        if let Some(layout) = text(ctx, format_x(min_x, by_date), TEXT_COLOR) {
+            ctx.draw_text(&layout, (plot.x0, plot.y1 + 4.0));
+        }
+        if max_x > min_x {
+            if let Some(layout) = text(ctx, format_x(max_x, by_date), TEXT_COLOR) {
+                ctx.draw_text(&layout, (plot.x1 - layout.size().width, plot.y1 + 4.0));
+            }
+        }
+        ctx.stroke(plot, &BUTTON_BORDER, 1.0);
+
+        let mut rolling_average = BezPath::new();
+        let mut sum_of_best = BezPath::new();
+        let mut sum_of_best_started = false;
+        let mut personal_best = BezPath::new();
+        let mut last_personal_best = None;
+        for (i, (x, attempt)) in data.plotted().enumerate() {
+            let point = scale.point(x, attempt.rolling_average);
+            if i == 0 {
+                rolling_average.move_to(point);
+            } else {
+                rolling_average.line_to(point);
+            }
+
+            match attempt.sum_of_best {
+                Some(y) if sum_of_best_started => sum_of_best.line_to(scale.point(x, y)),
+                Some(y) => {
+                    sum_of_best.move_to(scale.point(x, y));
+                    sum_of_best_started = true;
+                }
+                None => sum_of_best_started = false,
+            }
+
+            if attempt.is_personal_best {
+                let point = scale.point(x, attempt.time);
+                match last_personal_best {
+                    Some(last) => {
+                        personal_best.line_to(Point::new(point.x, last));
+                        personal_best.line_to(point);
+                    }
+                    None => personal_best.move_to(point),
+                }
+                last_personal_best = Some(point.y);
+            }
+        }
+        if let Some(last) = last_personal_best {
+            personal_best.line_to(Point::new(plot.x1, last));
+        }
+
+        ctx.stroke(&sum_of_best, &SUM_OF_BEST_COLOR, 1.5);
+        ctx.stroke(&rolling_average, &ROLLING_AVERAGE_COLOR, 1.5);
+        ctx.stroke(&personal_best, &PERSONAL_BEST_COLOR, 1.5);
+
+        for (x, attempt) in data.plotted() {
+            let point = scale.point(x, attempt.time);
+            if attempt.is_personal_best {
+                ctx.fill(Circle::new(point, 4.0), &PERSONAL_BEST_COLOR);
+            } else {
+                ctx.fill(Circle::new(point, 2.5), &ATTEMPT_COLOR);
+            }
+        }
+
+        let mut legend_x = plot.x0 + BUTTON_SPACING;
+        for (name, color) in [
+            (String::from("Attempt"), ATTEMPT_COLOR),
+            (String::from("Personal Best"), PERSONAL_BEST_COLOR),
+            (
+                format!("Average of {ROLLING_ATTEMPTS}"),
+                ROLLING_AVERAGE_COLOR,
+            ),
+            (String::from("Sum of Best"), SUM_OF_BEST_COLOR),
+        ] {
+            if let Some(layout) = text(ctx, name, TEXT_COLOR) {
+                let center_y = plot.y0 + BUTTON_SPACING + layout.size().height / 2.0;
+                ctx.fill(Circle::new((legend_x + 4.0, center_y), 4.0), &color);
+                ctx.draw_text(&layout, (legend_x + 12.0, plot.y0 + BUTTON_SPACING));
+                legend_x += layout.size().width + 12.0 + 2.0 * BUTTON_SPACING;
+            }
+        }
+    }
+}
+
+pub fn root_widget() -> impl Widget<State> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(
+            Flex::row()
+                .with_child(Label::new("Timing Method"))
+                .with_spacer(BUTTON_SPACING)
+                .with_child(
+                    combo_box::static_list(TIMING_METHOD_NAMES)
+                        .lens(State::timing_method)
+                        .fix_width(120.0),
+                )
+                .with_spacer(2.0 * BUTTON_SPACING)
+                .with_child(Label::new("Plot Over"))
+                .with_spacer(BUTTON_SPACING)
+                .with_child(
+                    combo_box::static_list(X_AXES)
+                        .lens(State::x_axis)
+                        .fix_width(120.0),
+                ),
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_flex_child(Chart.border(BUTTON_BORDER, 1.0), 1.0)
+        .padding(MARGIN)
+        .controller(Refresh::new(REFRESH_INTERVAL, State::refresh))
+}
+
+#[cfg(test)]
+mod tests {
+    use livesplit_core::{Segment, Time};
+
+    use super::*;
+
+    fn time(seconds: f64) -> Time {
+        Time::new().with_real_time(Some(TimeSpan::from_seconds(seconds)))
+    }
+
+    /// Adds the finished attempts with the given real times, numbered from 1
+    /// on. A missing time is an attempt that wasn't finished.
+    fn run_with_attempts(times: &[Option<f64>]) -> Run {
+        let mut run = Run::new();
+        run.push_segment(Segment::new("Split"));
+        for (index, &seconds) in times.iter().enumerate() {
+            let time = seconds.map_or_else(Time::new, time);
+            run.add_attempt_with_index(time, index as i32 + 1, None, None, None);
+        }
+        run
+    }
+
+    #[test]
+    fn marks_personal_bests_of_finished_attempts() {
+        let run = run_with_attempts(&[Some(100.0), None, Some(90.0), Some(95.0), Some(80.0)]);
+        let attempts = attempts(&run, TimingMethod::RealTime);
+
+        let indices: Vec<_> = attempts.iter().map(|attempt| attempt.index).collect();
+        assert_eq!(indices, [1, 3, 4, 5]);
+        let personal_bests: Vec<_> = attempts
+            .iter()
+            .map(|attempt| attempt.is_personal_best)
+            .collect();
+        assert_eq!(personal_bests, [true, true, false, true]);
+        assert!(attempts(&run, TimingMethod::GameTime).is_empty());
+    }
+
+    #[test]
+    fn averages_the_latest_attempts() {
+        let times: Vec<_> = (1..=ROLLING_ATTEMPTS + 2)
+            .map(|seconds| Some(seconds as f64))
+            .collect();
+        let attempts = attempts(&run_with_attempts(&times), TimingMethod::RealTime);
+
+        assert_eq!(attempts[0].rolling_average, 1.0);
+        assert_eq!(attempts[1].rolling_average, 1.5);
+        // The average of 3 to 12.
+        assert_eq!(attempts.last().unwrap().rolling_average, 7.5);
+    }
+
+    #[test]
+    fn sums_the_best_segments_known_at_each_attempt() {
+        let mut run = Run::new();
+        // The times with ids of 0 and below are from before the history.
+        for history in [
+            [(-1, 40.0), (1, 45.0), (3, 38.0)],
+            [(0, 60.0), (1, 55.0), (4, 70.0)],
+        ] {
+            let mut segment = Segment::new("");
+            for (id, seconds) in history {
+                segment.segment_history_mut().insert(id, time(seconds));
+            }
+            run.push_segment(segment);
+        }
+
+        let mut sum_of_best = SumOfBest::new(&run, TimingMethod::RealTime);
+        assert_eq!(sum_of_best.at(1), Some(95.0));
+        assert_eq!(sum_of_best.at(3), Some(93.0));
+        assert_eq!(sum_of_best.at(4), Some(93.0));
+        assert_eq!(SumOfBest::new(&run, TimingMethod::GameTime).at(4), None);
+    }
+
+    #[test]
+    fn sum_of_best_needs_every_segment() {
+        let mut run = Run::new();
+        for id in [2, 1] {
+            let mut segment = Segment::new("");
+            segment.segment_history_mut().insert(id, time(10.0));
+            run.push_segment(segment);
+        }
+
+        let mut sum_of_best = SumOfBest::new(&run, TimingMethod::RealTime);
+        assert_eq!(sum_of_best.at(1), None);
+        assert_eq!(sum_of_best.at(2), Some(20.0));
+    }
+}
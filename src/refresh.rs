@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+use druid::{widget::Controller, Env, Event, EventCtx, TimerToken, Widget};
+
+/// Periodically calls back into the window's state, so windows that show
+/// state living outside of druid, like the timer or the log, stay up to date.
+pub struct Refresh<F> {
+    interval: Duration,
+    timer: TimerToken,
+    refresh: F,
+}
+
+impl<F> Refresh<F> {
+    pub fn new(interval: Duration, refresh: F) -> Self {
+        Self {
+            interval,
+            timer: TimerToken::INVALID,
+            refresh,
+        }
+    }
+}
+
+impl<T, W: Widget<T>, F: FnMut(&mut T)> Controller<T, W> for Refresh<F> {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        match event {
+            Event::WindowConnected => {
+                (self.refresh)(data);
+                self.timer = ctx.request_timer(self.interval);
+            }
+            Event::Timer(token) if *token == self.timer => {
+                (self.refresh)(data);
+                self.timer = ctx.request_timer(self.interval);
+                return;
+            }
+            _ => {}
+        }
+        child.event(ctx, event, data, env)
+    }
+}
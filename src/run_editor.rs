@@ -1,5 +1,6 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, collections::BTreeSet, fmt::Write, fs, rc::Rc};
 
+use anyhow::Context;
 use druid::{
     commands,
     lens::Identity,
@@ -7,21 +8,25 @@ use druid::{
     text::{Formatter, ParseFormatter, Selection, Validation, ValidationError},
     theme,
     widget::{
-        Button, ClipBox, Container, CrossAxisAlignment, FillStrat, Flex, Label, List, ListIter,
-        MainAxisAlignment, Painter, Scroll, Switch, TextBox,
+        Button, ClipBox, Container, Controller, CrossAxisAlignment, Either, FillStrat, Flex, Label,
+        LineBreaking, List, ListIter, MainAxisAlignment, Painter, Scroll, SizedBox, Switch,
+        TextBox,
     },
-    BoxConstraints, Color, Data, Env, Event, EventCtx, ImageBuf, LayoutCtx, Lens, LensExt,
-    LifeCycle, LifeCycleCtx, LinearGradient, Menu, MenuItem, PaintCtx, RenderContext, Selector,
-    Size, TextAlignment, UnitPoint, UpdateCtx, Widget, WidgetExt,
+    BoxConstraints, Color, Data, Env, Event, EventCtx, FileDialogOptions, FileInfo, FileSpec,
+    ImageBuf, LayoutCtx, Lens, LensExt, LifeCycle, LifeCycleCtx, LinearGradient, Menu, MenuItem,
+    PaintCtx, RenderContext, Selector, Size, TextAlignment, UnitPoint, UpdateCtx, Widget,
+    WidgetExt,
 };
 use livesplit_core::{
     run::editor,
     timing::formatter::{none_wrapper::EmptyWrapper, Accuracy, SegmentTime, TimeFormatter},
-    RunEditor, TimeSpan, TimingMethod,
+    Run, RunEditor, TimeSpan, TimingMethod,
 };
 
 use crate::{
-    config::Config,
+    bulk_edit::{self, Operation},
+    combo_box,
+    config::{show_error, Config},
     consts::{
         switch_style, ATTEMPTS_OFFSET_WIDTH, BUTTON_ACTIVE_BOTTOM, BUTTON_ACTIVE_TOP,
         BUTTON_BORDER, BUTTON_HEIGHT, BUTTON_SPACING, COLUMN_LABEL_FONT, DIALOG_BUTTON_HEIGHT,
@@ -29,9 +34,18 @@ use crate::{
         TIME_COLUMN_WIDTH,
     },
     formatter_scope::{self, formatted, optional_time_span, validated, OnFocusLoss},
+    notes::Notes,
+    sections::Sections,
+    segment_edit,
+    segment_import::{self, Column, ImportedSegment},
+    striped_row::stripe_color,
     LayoutData, MainState,
 };
 
+const NOTES_HEIGHT: f64 = 80.0;
+const SECTION_INDENT: f64 = 20.0;
+const SECTION_ROW_HEIGHT: f64 = 26.0;
+
 struct SegmentWidget<T> {
     inner: T,
 }
@@ -95,32 +109,95 @@ impl<T: Widget<Segment>> Widget<Segment> for SegmentWidget<T> {
     }
 
     fn paint(&mut self, ctx: &mut PaintCtx, data: &Segment, env: &Env) {
-        let rect = ctx.size().to_rect();
-        if data.state.segments[data.index]
+        let is_selected = data.state.segments[data.index]
             .selected
-            .is_selected_or_active()
-        {
-            ctx.fill(
-                rect,
-                &LinearGradient::new(
-                    UnitPoint::TOP,
-                    UnitPoint::BOTTOM,
-                    (Color::rgb8(0x33, 0x73, 0xf4), Color::rgb8(0x15, 0x35, 0x74)),
-                ),
-            );
-        } else {
-            let color = if data.index & 1 == 0 {
-                Color::grey8(0x12)
+            .is_selected_or_active();
+        paint_row_background(ctx, is_selected, data.index);
+        self.inner.paint(ctx, data, env)
+    }
+}
+
+fn paint_row_background(ctx: &mut PaintCtx, is_selected: bool, index: usize) {
+    let rect = ctx.size().to_rect();
+    if is_selected {
+        ctx.fill(
+            rect,
+            &LinearGradient::new(
+                UnitPoint::TOP,
+                UnitPoint::BOTTOM,
+                (Color::rgb8(0x33, 0x73, 0xf4), Color::rgb8(0x15, 0x35, 0x74)),
+            ),
+        );
+    } else {
+        ctx.fill(rect, &stripe_color(index));
+    }
+}
+
+struct SectionRowWidget<T> {
+    inner: T,
+}
+
+impl<T> SectionRowWidget<T> {
+    fn new(inner: T) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T: Widget<SectionRow>> Widget<SectionRow> for SectionRowWidget<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut SectionRow, env: &Env) {
+        if let Event::MouseDown(event) = event {
+            if data.section_start.is_some() {
+                data.toggle_collapsed = true;
+            } else if event.mods.shift() {
+                data.select_range = true;
+            } else if event.mods.ctrl() {
+                data.select_additionally = true;
             } else {
-                Color::grey8(0xb)
-            };
-            ctx.fill(rect, &color);
+                data.select_only = true;
+            }
+        }
+        self.inner.event(ctx, event, data, env)
+    }
+
+    fn lifecycle(
+        &mut self,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &SectionRow,
+        env: &Env,
+    ) {
+        self.inner.lifecycle(ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &SectionRow, data: &SectionRow, env: &Env) {
+        if !old_data.same(data) {
+            ctx.request_paint();
+        }
+        self.inner.update(ctx, old_data, data, env)
+    }
+
+    fn layout(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &SectionRow,
+        env: &Env,
+    ) -> Size {
+        self.inner.layout(ctx, bc, data, env)
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &SectionRow, env: &Env) {
+        if data.section_start.is_some() {
+            let rect = ctx.size().to_rect();
+            ctx.fill(rect, &Color::grey8(0x21));
+        } else {
+            paint_row_background(ctx, data.is_selected, data.index);
         }
         self.inner.paint(ctx, data, env)
     }
 }
 
-#[derive(Clone, Data)]
+#[derive(Clone, Data, Lens)]
 pub struct State {
     state: Rc<editor::State>,
     config: Rc<RefCell<Config>>,
@@ -129,11 +206,64 @@ pub struct State {
     pub editor: Rc<RefCell<Option<RunEditor>>>,
     #[data(ignore)]
     pub closed_with_ok: bool,
+    pub auto_splitter: String,
+    can_associate_auto_splitter: bool,
+    notes: Rc<Notes>,
+    bulk_edit: Option<BulkEdit>,
+    sections_view: bool,
+    /// The sections that are collapsed, by their first segment, as several
+    /// sections can have the same name.
+    collapsed_sections: Rc<BTreeSet<usize>>,
+    import: Option<Import>,
+}
+
+#[derive(Clone, Copy, PartialEq, Data)]
+enum BulkOperation {
+    Shift,
+    Scale,
+    CopyToGameTime,
+    CopyToRealTime,
+}
+
+#[derive(Clone, Data, Lens)]
+struct BulkEdit {
+    operation: BulkOperation,
+    value: String,
+    whole_run: bool,
+}
+
+#[derive(Clone, Data, Lens)]
+struct Import {
+    text: String,
+    /// The columns after the segment names, as indices into `Column::NAMES`.
+    first_column: usize,
+    second_column: usize,
+    third_column: usize,
+    has_header: bool,
+}
+
+impl Import {
+    fn parse(&self) -> Result<Vec<ImportedSegment>, String> {
+        let columns =
+            [self.first_column, self.second_column, self.third_column].map(Column::from_index);
+        segment_import::parse(&self.text, &columns, self.has_header)
+    }
 }
 
 impl State {
     pub fn new(mut editor: RunEditor, config: Rc<RefCell<Config>>) -> Self {
         let state = Rc::new(editor.state());
+        let (auto_splitter, can_associate_auto_splitter, notes) = {
+            let config = config.borrow();
+            (
+                config
+                    .auto_splitter_for_current_splits()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                config.can_associate_auto_splitter(),
+                Rc::new(config.notes().clone()),
+            )
+        };
         // let image = image::load_from_memory(state.icon_change.as_deref().unwrap())
         //     .unwrap()
         //     .into_rgba8();
@@ -150,8 +280,306 @@ impl State {
             // image,
             editor: Rc::new(RefCell::new(Some(editor))),
             closed_with_ok: false,
+            auto_splitter,
+            can_associate_auto_splitter,
+            notes,
+            bulk_edit: None,
+            sections_view: false,
+            collapsed_sections: Rc::default(),
+            import: None,
+        }
+    }
+
+    pub fn notes(&self) -> &Notes {
+        &self.notes
+    }
+
+    /// Applies the bulk edit to the run. The run editor can't do this, so it
+    /// gets recreated with the changed run, keeping the selection.
+    fn apply_bulk_edit(&mut self) {
+        let bulk_edit = match self.bulk_edit.take() {
+            Some(bulk_edit) => bulk_edit,
+            None => return,
+        };
+        let (operation, target) = match bulk_edit.operation {
+            BulkOperation::Shift => match bulk_edit.value.parse() {
+                Ok(offset) => (Operation::Shift(offset), self.state.timing_method),
+                Err(_) => return,
+            },
+            BulkOperation::Scale => match bulk_edit::parse_factor(&bulk_edit.value) {
+                Some(factor) => (Operation::Scale(factor), self.state.timing_method),
+                None => return,
+            },
+            BulkOperation::CopyToGameTime => (Operation::Copy, TimingMethod::GameTime),
+            BulkOperation::CopyToRealTime => (Operation::Copy, TimingMethod::RealTime),
+        };
+        let selected: Vec<bool> = self
+            .state
+            .segments
+            .iter()
+            .map(|segment| bulk_edit.whole_run || segment.selected.is_selected_or_active())
+            .collect();
+
+        self.edit_run(|run| {
+            bulk_edit::apply(run, &selected, operation, target);
+            None
+        });
+    }
+
+    /// Adds the imported segments after the last segment. When replacing, the
+    /// existing segments are removed afterwards, as the run editor always
+    /// needs to keep at least one segment.
+    fn import_segments(&mut self, replace: bool) {
+        let mut segments = match self.import.as_ref().map(Import::parse) {
+            Some(Ok(segments)) if !segments.is_empty() => segments,
+            _ => return,
+        };
+        self.import = None;
+
+        // The segment times of the first imported segments would count from
+        // the last split of the existing segments. When replacing, they are
+        // turned into split times counting from the start instead.
+        if replace {
+            segment_import::accumulate_segment_times(&mut segments);
+        }
+
+        let mut editor = self.editor.borrow_mut();
+        let editor = editor.as_mut().unwrap();
+        let old_len = self.state.segments.len();
+        editor.select_only(old_len - 1);
+
+        for segment in segments {
+            editor.insert_segment_below();
+            let mut row = editor.active_segment();
+            row.set_name(segment.name);
+            // The segment time only matters if there's no split time, as the
+            // split time already determines it.
+            if let Some(time) = segment.split_time {
+                row.set_split_time(Some(time));
+            } else if let Some(time) = segment.segment_time {
+                row.set_segment_time(Some(time));
+            }
+            if segment.best_segment_time.is_some() {
+                row.set_best_segment_time(segment.best_segment_time);
+            }
+        }
+
+        if replace {
+            editor.select_only(0);
+            editor.select_range(old_len - 1);
+            editor.remove_segments();
+            editor.select_only(0);
+        }
+
+        self.state = Rc::new(editor.state());
+    }
+
+    /// Merges the active segment with the one after it.
+    fn merge_with_next(&mut self) {
+        if let Some(index) = self.active_segment() {
+            self.edit_run(|run| segment_edit::merge_with_next(run, index).then_some(index));
+        }
+    }
+
+    /// Splits the active segment in two and selects the new first half.
+    fn split_segment(&mut self) {
+        if let Some(index) = self.active_segment() {
+            self.edit_run(|run| segment_edit::split(run, index).then_some(index));
+        }
+    }
+
+    /// Changes the run directly, for edits the run editor can't do. The
+    /// editor gets recreated with the changed run. Either the segment that is
+    /// returned gets selected, or the selection stays the same. Nothing is
+    /// applied to the splits until the editor is closed with OK, so Cancel
+    /// still undoes these edits.
+    fn edit_run(&mut self, edit: impl FnOnce(&mut Run) -> Option<usize>) {
+        let mut run_editor = self.editor.borrow_mut();
+        let mut run = run_editor.take().unwrap().close();
+        let select = edit(&mut run);
+        // None of the edits remove the last segment, so this can't fail.
+        let mut new_editor = RunEditor::new(run).unwrap();
+        new_editor.select_timing_method(self.state.timing_method);
+
+        match select {
+            Some(index) => new_editor.select_only(index),
+            None => self.restore_selection(&mut new_editor),
+        }
+
+        self.state = Rc::new(new_editor.state());
+        *run_editor = Some(new_editor);
+    }
+
+    /// Selects the segments in the editor that were selected before the last
+    /// edit.
+    fn restore_selection(&self, editor: &mut RunEditor) {
+        let active = self.active_segment().unwrap_or_default();
+        editor.select_only(active);
+        for (index, segment) in self.state.segments.iter().enumerate() {
+            if index != active && segment.selected.is_selected_or_active() {
+                editor.select_additionally(index);
+            }
+        }
+        // Selecting additionally makes the segment the active one.
+        editor.select_additionally(active);
+    }
+
+    fn sections(&self) -> Sections {
+        Sections::parse(self.state.segments.iter().map(|segment| &*segment.name))
+    }
+
+    fn selected_segments(&self) -> Vec<usize> {
+        self.state
+            .segments
+            .iter()
+            .enumerate()
+            .filter(|(_, segment)| segment.selected.is_selected_or_active())
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Rewrites the segment names after changing the sections. The notes
+    /// follow the segments to their new names.
+    fn edit_sections(&mut self, edit: impl FnOnce(&mut Sections)) {
+        let mut sections = self.sections();
+        edit(&mut sections);
+
+        let mut run_editor = self.editor.borrow_mut();
+        let run_editor = run_editor.as_mut().unwrap();
+        let mut notes = None;
+        for (index, (segment, name)) in self
+            .state
+            .segments
+            .iter()
+            .zip(sections.segment_names())
+            .enumerate()
+        {
+            if segment.name != name {
+                notes
+                    .get_or_insert_with(|| (*self.notes).clone())
+                    .rename(&segment.name, &name);
+                run_editor.select_only(index);
+                run_editor.active_segment().set_name(name);
+            }
+        }
+
+        if let Some(notes) = notes {
+            self.restore_selection(run_editor);
+            self.state = Rc::new(run_editor.state());
+            self.notes = Rc::new(notes);
+        }
+    }
+
+    /// Groups the segments from the first to the last selected one into a new
+    /// section, named after the last one.
+    fn create_section(&mut self) {
+        let selected = self.selected_segments();
+        if let (Some(&first), Some(&last)) = (selected.first(), selected.last()) {
+            self.edit_sections(|sections| {
+                let name = sections.name(last).to_owned();
+                sections.create(first, last, name);
+            });
         }
     }
+
+    fn active_section_name(&self) -> Option<String> {
+        let sections = self.sections();
+        let section = sections.section_of(self.active_segment()?)?;
+        Some(sections.section_name(section).to_owned())
+    }
+
+    fn rename_section(&mut self, name: String) {
+        if let Some(index) = self.active_segment() {
+            self.edit_sections(|sections| {
+                if let Some(section) = sections.section_of(index) {
+                    sections.rename(section, name);
+                }
+            });
+        }
+    }
+
+    fn dissolve_section(&mut self) {
+        if let Some(index) = self.active_segment() {
+            self.edit_sections(|sections| {
+                if let Some(section) = sections.section_of(index) {
+                    sections.dissolve(section);
+                }
+            });
+        }
+    }
+
+    /// Moves the selected segments into the neighbouring section. They are
+    /// indented from the bottom up, so a block of segments stays together.
+    fn indent_segments(&mut self) {
+        let selected = self.selected_segments();
+        self.edit_sections(|sections| {
+            for &index in selected.iter().rev() {
+                sections.indent(index);
+            }
+        });
+    }
+
+    fn outdent_segments(&mut self) {
+        let selected = self.selected_segments();
+        self.edit_sections(|sections| {
+            for &index in &selected {
+                sections.outdent(index);
+            }
+        });
+    }
+
+    /// The rows of the sections view. Every section gets a header that
+    /// collapses it, followed by its segments.
+    fn section_rows(&self) -> Vec<SectionRow> {
+        let sections = self.sections();
+        let segments = &self.state.segments;
+        let segment_row = |index: usize, label: String, indented| SectionRow {
+            index,
+            label,
+            split_time: segments[index].split_time.clone(),
+            section_start: None,
+            indented,
+            is_selected: segments[index].selected.is_selected_or_active(),
+            select_only: false,
+            select_additionally: false,
+            select_range: false,
+            toggle_collapsed: false,
+        };
+
+        let mut rows = Vec::new();
+        for group in sections.groups() {
+            if let Some(section) = group.section {
+                let name = sections.section_name(section);
+                let is_collapsed = self.collapsed_sections.contains(&group.start);
+                let arrow = if is_collapsed { '▸' } else { '▾' };
+                // The header shows the split time of the whole section.
+                let mut header = segment_row(group.end, format!("{arrow} {name}"), false);
+                header.section_start = Some(group.start);
+                header.is_selected = false;
+                rows.push(header);
+                if is_collapsed {
+                    continue;
+                }
+            }
+            for index in group.start..=group.end {
+                let label = sections.name(index).to_owned();
+                rows.push(segment_row(index, label, group.section.is_some()));
+            }
+        }
+        rows
+    }
+
+    fn active_segment(&self) -> Option<usize> {
+        self.state
+            .segments
+            .iter()
+            .position(|segment| segment.selected == editor::SelectionState::Active)
+    }
+
+    fn active_segment_name(&self) -> Option<&str> {
+        let index = self.active_segment()?;
+        Some(&self.state.segments[index].name)
+    }
 }
 
 fn game_icon() -> impl Widget<State> {
@@ -347,6 +775,25 @@ fn side_buttons() -> impl Widget<State> {
                 .fix_height(BUTTON_HEIGHT),
         )
         .with_spacer(BUTTON_SPACING)
+        .with_child(
+            Button::new("Merge With Next")
+                .on_click(|_, state: &mut State, _| state.merge_with_next())
+                .disabled_if(|state: &State, _| {
+                    state
+                        .active_segment()
+                        .map_or(true, |index| index + 1 >= state.state.segments.len())
+                })
+                .expand_width()
+                .fix_height(BUTTON_HEIGHT),
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            Button::new("Split Segment")
+                .on_click(|_, state: &mut State, _| state.split_segment())
+                .expand_width()
+                .fix_height(BUTTON_HEIGHT),
+        )
+        .with_spacer(BUTTON_SPACING)
         .with_child(
             Button::new("Move Up")
                 .on_click(|_, state: &mut State, _| {
@@ -414,11 +861,15 @@ impl ListIter<Segment> for State {
         let mut editor = self.editor.borrow_mut();
         let editor = editor.as_mut().unwrap();
         let mut changed = false;
+        let mut renamed_notes = None;
 
         for index in 0..self.data_len() {
             segment.index = index;
             cb(&mut segment, index);
             if let Some(new_name) = segment.new_name.take() {
+                renamed_notes
+                    .get_or_insert_with(|| (*self.notes).clone())
+                    .rename(&self.state.segments[index].name, &new_name);
                 editor.select_only(index);
                 editor.active_segment().set_name(new_name);
                 changed = true;
@@ -469,6 +920,9 @@ impl ListIter<Segment> for State {
         if changed {
             self.state = Rc::new(editor.state());
         }
+        if let Some(notes) = renamed_notes {
+            self.notes = Rc::new(notes);
+        }
     }
 
     fn data_len(&self) -> usize {
@@ -490,6 +944,78 @@ struct Segment {
     unselect: bool,
 }
 
+impl ListIter<SectionRow> for State {
+    fn for_each(&self, mut cb: impl FnMut(&SectionRow, usize)) {
+        for (index, row) in self.section_rows().iter().enumerate() {
+            cb(row, index);
+        }
+    }
+
+    fn for_each_mut(&mut self, mut cb: impl FnMut(&mut SectionRow, usize)) {
+        let mut rows = self.section_rows();
+        for (index, row) in rows.iter_mut().enumerate() {
+            cb(row, index);
+        }
+
+        let mut editor = self.editor.borrow_mut();
+        let editor = editor.as_mut().unwrap();
+        let mut changed = false;
+        let mut collapsed = None;
+
+        for row in rows {
+            if row.toggle_collapsed {
+                if let Some(start) = row.section_start {
+                    let collapsed =
+                        collapsed.get_or_insert_with(|| (*self.collapsed_sections).clone());
+                    if !collapsed.remove(&start) {
+                        collapsed.insert(start);
+                    }
+                }
+            }
+            if row.select_only {
+                editor.select_only(row.index);
+                changed = true;
+            }
+            if row.select_additionally {
+                editor.select_additionally(row.index);
+                changed = true;
+            }
+            if row.select_range {
+                editor.select_range(row.index);
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.state = Rc::new(editor.state());
+        }
+        if let Some(collapsed) = collapsed {
+            self.collapsed_sections = Rc::new(collapsed);
+        }
+    }
+
+    fn data_len(&self) -> usize {
+        self.section_rows().len()
+    }
+}
+
+#[derive(Clone, Data)]
+struct SectionRow {
+    /// The segment of the row. For the header of a section, that's the last
+    /// segment of the section.
+    index: usize,
+    label: String,
+    split_time: String,
+    /// The first segment of the section, if this is the header of one.
+    section_start: Option<usize>,
+    indented: bool,
+    is_selected: bool,
+    select_only: bool,
+    select_additionally: bool,
+    select_range: bool,
+    toggle_collapsed: bool,
+}
+
 fn segments() -> impl Widget<State> {
     Flex::column()
         .with_child(
@@ -610,6 +1136,116 @@ fn segments() -> impl Widget<State> {
         })
 }
 
+fn section_actions() -> impl Widget<State> {
+    Flex::row()
+        .with_child(Label::new("Section"))
+        .with_spacer(BUTTON_SPACING)
+        .with_flex_child(
+            TextBox::new()
+                .lens(Identity.map(
+                    |state: &State| state.active_section_name().unwrap_or_default(),
+                    |state: &mut State, name: String| {
+                        if state
+                            .active_section_name()
+                            .map_or(false, |old_name| old_name != name)
+                        {
+                            state.rename_section(name);
+                        }
+                    },
+                ))
+                .disabled_if(|state: &State, _| state.active_section_name().is_none())
+                .expand_width(),
+            1.0,
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            Button::new("Create")
+                .on_click(|_, state: &mut State, _| state.create_section())
+                .fix_height(BUTTON_HEIGHT),
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            Button::new("Dissolve")
+                .on_click(|_, state: &mut State, _| state.dissolve_section())
+                .disabled_if(|state: &State, _| state.active_section_name().is_none())
+                .fix_height(BUTTON_HEIGHT),
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            Button::new("Indent")
+                .on_click(|_, state: &mut State, _| state.indent_segments())
+                .fix_height(BUTTON_HEIGHT),
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            Button::new("Outdent")
+                .on_click(|_, state: &mut State, _| state.outdent_segments())
+                .fix_height(BUTTON_HEIGHT),
+        )
+}
+
+fn section_row_label() -> impl Widget<SectionRow> {
+    Label::new(|row: &SectionRow, _: &_| row.label.clone())
+        .with_line_break_mode(LineBreaking::Clip)
+        .expand_width()
+}
+
+/// The segments grouped into their sections. The names are shown without the
+/// prefixes of the naming convention, which are managed by the actions above
+/// the list.
+fn sections() -> impl Widget<State> {
+    Flex::column()
+        .with_child(section_actions())
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            Flex::row()
+                .with_spacer(TABLE_HORIZONTAL_MARGIN)
+                .with_flex_child(
+                    ClipBox::unmanaged(Label::new("Segment Name").with_font(COLUMN_LABEL_FONT))
+                        .expand_width(),
+                    1.0,
+                )
+                .with_spacer(GRID_BORDER)
+                .with_child(
+                    ClipBox::unmanaged(Label::new("Split Time").with_font(COLUMN_LABEL_FONT))
+                        .align_right()
+                        .fix_width(TIME_COLUMN_WIDTH),
+                )
+                .with_spacer(TABLE_HORIZONTAL_MARGIN)
+                .fix_height(26.0)
+                .border(BUTTON_BORDER, 1.0),
+        )
+        .with_flex_child(
+            Scroll::new(
+                List::new(|| {
+                    SectionRowWidget::new(
+                        Flex::row()
+                            .with_spacer(TABLE_HORIZONTAL_MARGIN)
+                            .with_flex_child(
+                                Either::new(
+                                    |row: &SectionRow, _| row.indented,
+                                    section_row_label().padding((SECTION_INDENT, 0.0, 0.0, 0.0)),
+                                    section_row_label(),
+                                ),
+                                1.0,
+                            )
+                            .with_spacer(GRID_BORDER)
+                            .with_child(
+                                Label::new(|row: &SectionRow, _: &_| row.split_time.clone())
+                                    .align_right()
+                                    .fix_width(TIME_COLUMN_WIDTH),
+                            )
+                            .with_spacer(TABLE_HORIZONTAL_MARGIN)
+                            .fix_height(SECTION_ROW_HEIGHT),
+                    )
+                })
+                .border(BUTTON_BORDER, 1.0),
+            )
+            .vertical(),
+            1.0,
+        )
+}
+
 fn tabs() -> impl Widget<State> {
     Flex::column()
         .cross_axis_alignment(CrossAxisAlignment::Start)
@@ -645,11 +1281,63 @@ fn tabs() -> impl Widget<State> {
                             }
                         }),
                 )
+                .with_flex_spacer(1.0)
+                .with_child(Label::new("Group Sections"))
+                .with_spacer(BUTTON_SPACING)
+                .with_child(
+                    Switch::new()
+                        .lens(State::sections_view)
+                        .env_scope(|env, _| switch_style(env)),
+                )
                 .env_scope(|env, _| {
                     env.set(theme::BUTTON_BORDER_RADIUS, 0.0);
                 }),
         )
-        .with_flex_child(segments(), 1.0)
+        .with_flex_child(
+            Either::new(
+                |state: &State, _| state.sections_view,
+                sections(),
+                segments(),
+            ),
+            1.0,
+        )
+        .with_spacer(SPACING)
+        .with_child(notes())
+}
+
+fn notes() -> impl Widget<State> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(|state: &State, _: &_| {
+            match state.active_segment_name() {
+                Some(name) => format!("Notes for {name}"),
+                None => String::from("Notes"),
+            }
+        }))
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            TextBox::multiline()
+                .lens(Identity.map(
+                    |state: &State| {
+                        state
+                            .active_segment_name()
+                            .map(|name| state.notes.get(name).to_owned())
+                            .unwrap_or_default()
+                    },
+                    |state: &mut State, text: String| {
+                        if let Some(name) = state.active_segment_name().map(str::to_owned) {
+                            if state.notes.get(&name) != text {
+                                let mut notes = (*state.notes).clone();
+                                notes.set(&name, text);
+                                state.notes = Rc::new(notes);
+                            }
+                        }
+                    },
+                ))
+                .disabled_if(|state: &State, _| state.active_segment_name().is_none())
+                .expand_width()
+                .fix_height(NOTES_HEIGHT),
+        )
 }
 
 fn body() -> impl Widget<State> {
@@ -672,6 +1360,77 @@ fn run_editor() -> impl Widget<State> {
         .with_flex_child(body(), 1.0)
 }
 
+#[cfg(feature = "auto-splitting")]
+const SELECT_AUTO_SPLITTER: Selector<FileInfo> = Selector::new("run-editor-select-auto-splitter");
+
+#[cfg(feature = "auto-splitting")]
+struct AutoSplitterController;
+
+#[cfg(feature = "auto-splitting")]
+impl<W: Widget<State>> Controller<State, W> for AutoSplitterController {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut State,
+        env: &Env,
+    ) {
+        if let Event::Command(command) = event {
+            if let Some(file_info) = command.get(SELECT_AUTO_SPLITTER) {
+                data.auto_splitter = file_info.path().to_string_lossy().into_owned();
+                ctx.set_handled();
+                return;
+            }
+        }
+        child.event(ctx, event, data, env)
+    }
+}
+
+#[cfg(feature = "auto-splitting")]
+fn auto_splitter() -> impl Widget<State> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new("Auto Splitter"))
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            Flex::row()
+                .with_flex_child(
+                    TextBox::new()
+                        .with_placeholder("Uses the global auto splitter")
+                        .lens(State::auto_splitter)
+                        .expand_width(),
+                    1.0,
+                )
+                .with_spacer(BUTTON_SPACING)
+                .with_child(Button::new("Browse...").on_click(|ctx, _, _| {
+                    ctx.submit_command(
+                        commands::SHOW_OPEN_PANEL.with(
+                            FileDialogOptions::new()
+                                .title("Select Auto Splitter")
+                                .allowed_types(vec![
+                                    FileSpec {
+                                        name: "Auto Splitters",
+                                        extensions: &["wasm"],
+                                    },
+                                    FileSpec {
+                                        name: "All Files",
+                                        extensions: &["*.*"],
+                                    },
+                                ])
+                                .accept_command(SELECT_AUTO_SPLITTER),
+                        ),
+                    );
+                }))
+                .with_spacer(BUTTON_SPACING)
+                .with_child(Button::new("❌").on_click(|_, state: &mut State, _| {
+                    state.auto_splitter.clear();
+                })),
+        )
+        .disabled_if(|state: &State, _| !state.can_associate_auto_splitter)
+        .controller(AutoSplitterController)
+}
+
 struct Unwrap;
 
 impl<T> Lens<Option<T>, T> for Unwrap {
@@ -796,9 +1555,275 @@ fn link_layout_toggle() -> impl Widget<State> {
     LinkLayout(Switch::new().env_scope(|env, _| switch_style(env)))
 }
 
-pub fn root_widget() -> impl Widget<State> {
+fn bulk_edit_description(state: &State) -> String {
+    let bulk_edit = match &state.bulk_edit {
+        Some(bulk_edit) => bulk_edit,
+        None => return String::new(),
+    };
+    let timing_method = match state.state.timing_method {
+        TimingMethod::RealTime => "Real Time",
+        TimingMethod::GameTime => "Game Time",
+    };
+    match bulk_edit.operation {
+        BulkOperation::Shift => format!("Shift the {timing_method} segment times by"),
+        BulkOperation::Scale => format!("Scale the {timing_method} segment times by"),
+        BulkOperation::CopyToGameTime => String::from("Copy Real Time to Game Time"),
+        BulkOperation::CopyToRealTime => String::from("Copy Game Time to Real Time"),
+    }
+}
+
+fn is_valid_bulk_edit(bulk_edit: &BulkEdit) -> bool {
+    match bulk_edit.operation {
+        BulkOperation::Shift => bulk_edit.value.parse::<TimeSpan>().is_ok(),
+        BulkOperation::Scale => bulk_edit::parse_factor(&bulk_edit.value).is_some(),
+        BulkOperation::CopyToGameTime | BulkOperation::CopyToRealTime => true,
+    }
+}
+
+fn bulk_edit() -> impl Widget<State> {
+    let panel = Flex::row()
+        .with_child(Label::new(|state: &State, _: &_| {
+            bulk_edit_description(state)
+        }))
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            Either::new(
+                |bulk_edit: &BulkEdit, _| {
+                    matches!(
+                        bulk_edit.operation,
+                        BulkOperation::Shift | BulkOperation::Scale
+                    )
+                },
+                TextBox::new()
+                    .with_text_alignment(TextAlignment::End)
+                    .lens(BulkEdit::value)
+                    .fix_width(TIME_COLUMN_WIDTH),
+                SizedBox::empty(),
+            )
+            .lens(State::bulk_edit.then(Unwrap)),
+        )
+        .with_flex_spacer(1.0)
+        .with_child(
+            Switch::new()
+                .env_scope(|env, _| switch_style(env))
+                .lens(State::bulk_edit.then(Unwrap).then(BulkEdit::whole_run)),
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_child(Label::new("Whole Run"))
+        .with_spacer(SPACING)
+        .with_child(
+            Button::new("Apply")
+                .on_click(|_, state: &mut State, _| state.apply_bulk_edit())
+                .disabled_if(|state: &State, _| {
+                    !state.bulk_edit.as_ref().map_or(false, is_valid_bulk_edit)
+                }),
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_child(Button::new("Cancel").on_click(|_, state: &mut State, _| {
+            state.bulk_edit = None;
+        }));
+
+    Either::new(
+        |state: &State, _| state.bulk_edit.is_some(),
+        panel.padding((0.0, SPACING, 0.0, 0.0)),
+        SizedBox::empty(),
+    )
+}
+
+const LOAD_IMPORT_FILE: Selector<FileInfo> = Selector::new("run-editor-load-import-file");
+
+struct ImportController;
+
+impl<W: Widget<State>> Controller<State, W> for ImportController {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut State,
+        env: &Env,
+    ) {
+        if let Event::Command(command) = event {
+            if let Some(file_info) = command.get(LOAD_IMPORT_FILE) {
+                match fs::read_to_string(file_info.path()).context("Failed reading the file.") {
+                    Ok(text) => {
+                        if let Some(import) = &mut data.import {
+                            import.text = text;
+                        }
+                    }
+                    Err(e) => show_error(e),
+                }
+                ctx.set_handled();
+                return;
+            }
+        }
+        child.event(ctx, event, data, env)
+    }
+}
+
+fn import_preview(state: &State) -> String {
+    let import = match &state.import {
+        Some(import) => import,
+        None => return String::new(),
+    };
+    let segments = match import.parse() {
+        Ok(segments) => segments,
+        Err(e) => return e,
+    };
+
+    let format_time = |time: Option<TimeSpan>| SegmentTime::new().format(time).to_string();
+    let mut preview = String::new();
+    for (index, segment) in segments.iter().enumerate() {
+        let _ = write!(preview, "{}. {}", index + 1, segment.name);
+        for (name, time) in [
+            ("Split", segment.split_time),
+            ("Segment", segment.segment_time),
+            ("Best", segment.best_segment_time),
+        ] {
+            if time.is_some() {
+                let _ = write!(preview, "   {name} {}", format_time(time));
+            }
+        }
+        preview.push('\n');
+    }
+    preview
+}
+
+fn can_import(state: &State) -> bool {
+    state.import.as_ref().map_or(
+        false,
+        |import| matches!(import.parse(), Ok(segments) if !segments.is_empty()),
+    )
+}
+
+fn import_column(
+    label: &'static str,
+    lens: impl Lens<Import, usize> + 'static,
+) -> impl Widget<State> {
+    Flex::row()
+        .with_child(Label::new(label))
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            combo_box::static_list(Column::NAMES)
+                .lens(State::import.then(Unwrap).then(lens))
+                .fix_width(TIME_COLUMN_WIDTH),
+        )
+}
+
+/// Replaces the run editor while importing segments. The text is shown next to
+/// a preview of the segments it turns into.
+fn import() -> impl Widget<State> {
     Flex::column()
-        .with_flex_child(run_editor(), 1.0)
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(
+            Label::new(
+                "Paste the segments or load a file, one segment per line. The columns \
+            are separated by commas or tabs and start with the segment names.",
+            )
+            .with_line_break_mode(LineBreaking::WordWrap),
+        )
+        .with_spacer(SPACING)
+        .with_child(
+            Flex::row()
+                .with_child(import_column("Column 2", Import::first_column))
+                .with_spacer(SPACING)
+                .with_child(import_column("Column 3", Import::second_column))
+                .with_spacer(SPACING)
+                .with_child(import_column("Column 4", Import::third_column))
+                .with_flex_spacer(1.0)
+                .with_child(
+                    Switch::new()
+                        .env_scope(|env, _| switch_style(env))
+                        .lens(State::import.then(Unwrap).then(Import::has_header)),
+                )
+                .with_spacer(BUTTON_SPACING)
+                .with_child(Label::new("Header Line")),
+        )
+        .with_spacer(SPACING)
+        .with_flex_child(
+            Flex::row()
+                .cross_axis_alignment(CrossAxisAlignment::Start)
+                .with_flex_child(
+                    TextBox::multiline()
+                        .lens(State::import.then(Unwrap).then(Import::text))
+                        .expand(),
+                    1.0,
+                )
+                .with_spacer(SPACING)
+                .with_flex_child(
+                    Scroll::new(
+                        Label::new(|state: &State, _: &_| import_preview(state))
+                            .with_line_break_mode(LineBreaking::WordWrap)
+                            .expand_width()
+                            .padding(BUTTON_SPACING),
+                    )
+                    .vertical()
+                    .expand()
+                    .border(BUTTON_BORDER, 1.0),
+                    1.0,
+                ),
+            1.0,
+        )
+        .with_spacer(SPACING)
+        .with_child(
+            Flex::row()
+                .with_child(Button::new("Load File...").on_click(|ctx, _, _| {
+                    ctx.submit_command(
+                        commands::SHOW_OPEN_PANEL.with(
+                            FileDialogOptions::new()
+                                .title("Import Segments")
+                                .allowed_types(vec![
+                                    FileSpec {
+                                        name: "CSV",
+                                        extensions: &["csv"],
+                                    },
+                                    FileSpec {
+                                        name: "Text",
+                                        extensions: &["txt"],
+                                    },
+                                    FileSpec {
+                                        name: "All Files",
+                                        extensions: &["*.*"],
+                                    },
+                                ])
+                                .accept_command(LOAD_IMPORT_FILE),
+                        ),
+                    );
+                }))
+                .with_flex_spacer(1.0)
+                .with_child(
+                    Button::new("Append")
+                        .on_click(|_, state: &mut State, _| state.import_segments(false))
+                        .disabled_if(|state: &State, _| !can_import(state)),
+                )
+                .with_spacer(BUTTON_SPACING)
+                .with_child(
+                    Button::new("Replace")
+                        .on_click(|_, state: &mut State, _| state.import_segments(true))
+                        .disabled_if(|state: &State, _| !can_import(state)),
+                )
+                .with_spacer(BUTTON_SPACING)
+                .with_child(Button::new("Cancel").on_click(|_, state: &mut State, _| {
+                    state.import = None;
+                })),
+        )
+        .controller(ImportController)
+}
+
+pub fn root_widget() -> impl Widget<State> {
+    let column = Flex::column()
+        .with_flex_child(
+            Either::new(
+                |state: &State, _| state.import.is_some(),
+                import(),
+                run_editor(),
+            ),
+            1.0,
+        )
+        .with_child(bulk_edit());
+    #[cfg(feature = "auto-splitting")]
+    let column = column.with_spacer(SPACING).with_child(auto_splitter());
+    column
         .with_spacer(MARGIN)
         .with_child(
             Flex::row()
@@ -840,6 +1865,8 @@ const CLEAR_HISTORY: Selector = Selector::new("run-editor-clear-history");
 const CLEAR_TIMES: Selector = Selector::new("run-editor-clear-times");
 const CLEAN_SUM_OF_BEST: Selector = Selector::new("run-editor-clean-sum-of-best");
 const GENERATE_GOAL_COMPARISON: Selector = Selector::new("run-editor-generate-goal-comparison");
+const BULK_EDIT: Selector<BulkOperation> = Selector::new("run-editor-bulk-edit");
+const IMPORT_SEGMENTS: Selector = Selector::new("run-editor-import-segments");
 
 impl<T: Widget<State>> Widget<State> for OtherButtonWidget<T> {
     fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut State, env: &Env) {
@@ -857,7 +1884,26 @@ impl<T: Widget<State>> Widget<State> for OtherButtonWidget<T> {
                         MenuItem::new("Generate Goal Comparison")
                             .command(GENERATE_GOAL_COMPARISON)
                             .enabled(false),
-                    ),
+                    )
+                    .separator()
+                    .entry(
+                        MenuItem::new("Shift Times...")
+                            .command(BULK_EDIT.with(BulkOperation::Shift)),
+                    )
+                    .entry(
+                        MenuItem::new("Scale Times...")
+                            .command(BULK_EDIT.with(BulkOperation::Scale)),
+                    )
+                    .entry(
+                        MenuItem::new("Copy Real Time to Game Time...")
+                            .command(BULK_EDIT.with(BulkOperation::CopyToGameTime)),
+                    )
+                    .entry(
+                        MenuItem::new("Copy Game Time to Real Time...")
+                            .command(BULK_EDIT.with(BulkOperation::CopyToRealTime)),
+                    )
+                    .separator()
+                    .entry(MenuItem::new("Import Segments...").command(IMPORT_SEGMENTS)),
                 event.window_pos,
             );
             return;
@@ -872,6 +1918,24 @@ impl<T: Widget<State>> Widget<State> for OtherButtonWidget<T> {
                 let editor = editor.as_mut().unwrap();
                 editor.clear_times();
                 data.state = Rc::new(editor.state());
+            } else if let Some(&operation) = command.get(BULK_EDIT) {
+                data.bulk_edit = Some(BulkEdit {
+                    operation,
+                    value: String::from(match operation {
+                        BulkOperation::Scale => "1",
+                        _ => "0",
+                    }),
+                    whole_run: false,
+                });
+            } else if command.is(IMPORT_SEGMENTS) {
+                data.bulk_edit = None;
+                data.import = Some(Import {
+                    text: String::new(),
+                    first_column: 1,
+                    second_column: 2,
+                    third_column: 3,
+                    has_header: false,
+                });
             }
         }
         self.inner.event(ctx, event, data, env)
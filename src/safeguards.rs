@@ -0,0 +1,195 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use livesplit_core::{
+    analysis::state_helper::last_delta, run::PERSONAL_BEST_COMPARISON_NAME, TimeSpan, Timer,
+    TimerPhase,
+};
+use serde::{Deserialize, Serialize};
+
+/// Protection against splitting twice because of a bouncing key and against
+/// resetting by accident. The durations are in milliseconds, with 0 turning
+/// the safeguard off.
+#[derive(Copy, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct Safeguards {
+    pub split_cooldown: u64,
+    pub reset_twice_within: u64,
+    pub confirm_reset_ahead_of_pb: bool,
+}
+
+pub enum Reset {
+    Allowed,
+    NeedsConfirmation,
+    Ignored,
+}
+
+struct State {
+    safeguards: Safeguards,
+    last_split: Option<Instant>,
+    reset_pressed: Option<Instant>,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    safeguards: Safeguards {
+        split_cooldown: 0,
+        reset_twice_within: 0,
+        confirm_reset_ahead_of_pb: false,
+    },
+    last_split: None,
+    reset_pressed: None,
+});
+
+pub fn configure(safeguards: Safeguards) {
+    let mut state = STATE.lock().unwrap();
+    state.safeguards = safeguards;
+    state.reset_pressed = None;
+}
+
+fn within(instant: Option<Instant>, millis: u64) -> bool {
+    millis != 0 && instant.map_or(false, |i| i.elapsed() < Duration::from_millis(millis))
+}
+
+/// Starts the timer or splits, unless the previous split was too recent.
+pub fn split_or_start(timer: &mut Timer) {
+    let mut state = STATE.lock().unwrap();
+    if timer.current_phase() == TimerPhase::Running
+        && within(state.last_split, state.safeguards.split_cooldown)
+    {
+        log::info!("Ignored a split right after the previous one.");
+        return;
+    }
+    timer.split_or_start();
+    state.last_split = Some(Instant::now());
+}
+
+/// Checks whether pressing reset should go through. With the two presses
+/// safeguard, the first press is only remembered. A run that has ended can
+/// always be reset.
+pub fn check_reset(timer: &Timer) -> Reset {
+    let mut state = STATE.lock().unwrap();
+    if !is_in_progress(timer) {
+        return Reset::Allowed;
+    }
+
+    let within_reset = state.safeguards.reset_twice_within;
+    if within_reset != 0 {
+        if !within(state.reset_pressed, within_reset) {
+            state.reset_pressed = Some(Instant::now());
+            return Reset::Ignored;
+        }
+        state.reset_pressed = None;
+    }
+
+    confirmation(&state.safeguards, timer)
+}
+
+/// Checks whether an action that resets the timer along the way, like opening
+/// other splits or exiting, should go through. Those can't be pressed twice,
+/// so only the confirmation applies to them.
+pub fn check_implicit_reset(timer: &Timer) -> Reset {
+    let state = STATE.lock().unwrap();
+    if !is_in_progress(timer) {
+        return Reset::Allowed;
+    }
+    confirmation(&state.safeguards, timer)
+}
+
+fn is_in_progress(timer: &Timer) -> bool {
+    matches!(
+        timer.current_phase(),
+        TimerPhase::Running | TimerPhase::Paused
+    )
+}
+
+fn confirmation(safeguards: &Safeguards, timer: &Timer) -> Reset {
+    if safeguards.confirm_reset_ahead_of_pb && is_ahead_of_personal_best(timer) {
+        Reset::NeedsConfirmation
+    } else {
+        Reset::Allowed
+    }
+}
+
+fn is_ahead_of_personal_best(timer: &Timer) -> bool {
+    let split_index = match timer.current_split_index() {
+        Some(index) => index,
+        None => return false,
+    };
+    last_delta(
+        timer.run(),
+        split_index,
+        PERSONAL_BEST_COMPARISON_NAME,
+        timer.current_timing_method(),
+    )
+    .map_or(false, |delta| delta < TimeSpan::zero())
+}
+
+pub fn confirm_reset() -> bool {
+    native_dialog::MessageDialog::new()
+        .set_title("Reset?")
+        .set_text("You are ahead of your personal best. Do you really want to reset?")
+        .set_type(native_dialog::MessageType::Warning)
+        .show_confirm()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use livesplit_core::{Run, Segment};
+
+    use super::*;
+
+    // The safeguards are shared by the whole application, so the tests can't
+    // configure them at the same time.
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    fn timer(segments: usize) -> Timer {
+        let mut run = Run::new();
+        for index in 0..segments {
+            run.push_segment(Segment::new(format!("Segment {index}")));
+        }
+        Timer::new(run).unwrap()
+    }
+
+    fn reset_twice() -> Safeguards {
+        Safeguards {
+            reset_twice_within: 60_000,
+            ..Safeguards::default()
+        }
+    }
+
+    #[test]
+    fn reset_needs_two_presses_while_running() {
+        let _lock = LOCK.lock().unwrap();
+        configure(reset_twice());
+        let mut timer = timer(2);
+        timer.start();
+        assert!(matches!(check_reset(&timer), Reset::Ignored));
+        assert!(matches!(check_reset(&timer), Reset::Allowed));
+        assert!(matches!(check_reset(&timer), Reset::Ignored));
+    }
+
+    #[test]
+    fn ended_runs_reset_right_away() {
+        let _lock = LOCK.lock().unwrap();
+        configure(reset_twice());
+        let mut timer = timer(1);
+        timer.start();
+        timer.split();
+        assert_eq!(timer.current_phase(), TimerPhase::Ended);
+        assert!(matches!(check_reset(&timer), Reset::Allowed));
+    }
+
+    #[test]
+    fn implicit_resets_ignore_the_two_presses() {
+        let _lock = LOCK.lock().unwrap();
+        configure(reset_twice());
+        let mut timer = timer(2);
+        timer.start();
+        assert!(matches!(check_implicit_reset(&timer), Reset::Allowed));
+        // They also don't count as the first press.
+        assert!(matches!(check_reset(&timer), Reset::Ignored));
+    }
+}
@@ -0,0 +1,256 @@
+/// The sections of a run, following the naming convention of the Subsplits
+/// component. Every segment of a section but the last one has a name starting
+/// with `-`, and the last one may carry the section's name in braces, like
+/// `{Section}Segment`. Without that, the section is named after its last
+/// segment.
+#[derive(Clone)]
+pub struct Sections {
+    segments: Vec<Item>,
+    names: Vec<String>,
+}
+
+#[derive(Clone)]
+struct Item {
+    name: String,
+    section: Option<usize>,
+}
+
+/// A section, or a segment that isn't part of any.
+pub struct Group {
+    pub section: Option<usize>,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Sections {
+    pub fn parse<'a>(segment_names: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut sections = Self {
+            segments: Vec::new(),
+            names: Vec::new(),
+        };
+        let mut open = None;
+
+        for name in segment_names {
+            if let Some(name) = name.strip_prefix('-') {
+                let section = *open.get_or_insert_with(|| sections.add_section(String::new()));
+                sections.push(name, Some(section));
+            } else if let Some((section_name, name)) =
+                name.strip_prefix('{').and_then(|name| name.split_once('}'))
+            {
+                let section = open
+                    .take()
+                    .unwrap_or_else(|| sections.add_section(String::new()));
+                sections.names[section] = section_name.to_owned();
+                sections.push(name, Some(section));
+            } else if let Some(section) = open.take() {
+                sections.names[section] = name.to_owned();
+                sections.push(name, Some(section));
+            } else {
+                sections.push(name, None);
+            }
+        }
+
+        // The last section isn't closed, which the Subsplits component doesn't
+        // handle well, but it's still a section.
+        if let (Some(section), Some(last)) = (open, sections.segments.last()) {
+            sections.names[section] = last.name.clone();
+        }
+
+        sections
+    }
+
+    fn add_section(&mut self, name: String) -> usize {
+        self.names.push(name);
+        self.names.len() - 1
+    }
+
+    fn push(&mut self, name: &str, section: Option<usize>) {
+        self.segments.push(Item {
+            name: name.to_owned(),
+            section,
+        });
+    }
+
+    pub fn groups(&self) -> Vec<Group> {
+        let mut groups = Vec::<Group>::new();
+        for (index, segment) in self.segments.iter().enumerate() {
+            match groups.last_mut() {
+                Some(group) if group.section.is_some() && group.section == segment.section => {
+                    group.end = index;
+                }
+                _ => groups.push(Group {
+                    section: segment.section,
+                    start: index,
+                    end: index,
+                }),
+            }
+        }
+        groups
+    }
+
+    /// The names of the segments following the convention. The section's name
+    /// is left out if it's the name of its last segment anyway, unless that's
+    /// the only segment, as it wouldn't be a section then.
+    pub fn segment_names(&self) -> Vec<String> {
+        let mut names = Vec::with_capacity(self.segments.len());
+        for group in self.groups() {
+            for index in group.start..=group.end {
+                let name = &self.segments[index].name;
+                names.push(match group.section {
+                    Some(section)
+                        if index == group.end
+                            && group.start != group.end
+                            && self.names[section] == *name =>
+                    {
+                        name.clone()
+                    }
+                    Some(section) if index == group.end => {
+                        format!("{{{}}}{name}", self.names[section])
+                    }
+                    Some(_) => format!("-{name}"),
+                    None => name.clone(),
+                });
+            }
+        }
+        names
+    }
+
+    /// The name of the segment without the section.
+    pub fn name(&self, index: usize) -> &str {
+        &self.segments[index].name
+    }
+
+    pub fn section_of(&self, index: usize) -> Option<usize> {
+        self.segments.get(index)?.section
+    }
+
+    pub fn section_name(&self, section: usize) -> &str {
+        &self.names[section]
+    }
+
+    /// Turns the segments from the first to the last one into a section. They
+    /// are taken out of the sections they were part of.
+    pub fn create(&mut self, first: usize, last: usize, name: String) {
+        let section = self.add_section(name);
+        for segment in &mut self.segments[first..=last] {
+            segment.section = Some(section);
+        }
+    }
+
+    pub fn rename(&mut self, section: usize, name: String) {
+        self.names[section] = name;
+    }
+
+    pub fn dissolve(&mut self, section: usize) {
+        for segment in &mut self.segments {
+            if segment.section == Some(section) {
+                segment.section = None;
+            }
+        }
+    }
+
+    /// Moves the segment into the section after it, or else the one before
+    /// it. If there's neither, a new section is started with the segment.
+    pub fn indent(&mut self, index: usize) {
+        if self.segments[index].section.is_some() {
+            return;
+        }
+        let next = self.section_of(index + 1);
+        let previous = index
+            .checked_sub(1)
+            .and_then(|index| self.section_of(index));
+        let section = match next.or(previous) {
+            Some(section) => section,
+            None => {
+                let name = self.segments[index].name.clone();
+                self.add_section(name)
+            }
+        };
+        self.segments[index].section = Some(section);
+    }
+
+    /// Takes the segment out of its section. Taking it out of the middle of a
+    /// section splits the section in two.
+    pub fn outdent(&mut self, index: usize) {
+        let section = match self.segments[index].section.take() {
+            Some(section) => section,
+            None => return,
+        };
+        let rest = index + 1..self.segments.len();
+        if self.segments[rest.clone()]
+            .first()
+            .map_or(false, |segment| segment.section == Some(section))
+            && self.segments[..index]
+                .iter()
+                .any(|segment| segment.section == Some(section))
+        {
+            let name = self.names[section].clone();
+            let new_section = self.add_section(name);
+            for segment in &mut self.segments[rest] {
+                if segment.section != Some(section) {
+                    break;
+                }
+                segment.section = Some(new_section);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(names: &[&str]) -> Vec<String> {
+        Sections::parse(names.iter().copied()).segment_names()
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for names in [
+            &["-a", "-b", "Boss"][..],
+            &["-a", "-b", "{Level 1}Boss"],
+            &["Intro", "-a", "{World}b", "Outro"],
+            &["-a", "A", "-b", "B"],
+            &["{Solo}Solo"],
+            &["{Solo}Segment"],
+            &["Plain", "Segments"],
+            &[],
+        ] {
+            assert_eq!(round_trip(names), names);
+        }
+    }
+
+    #[test]
+    fn unclosed_section_gets_closed() {
+        let sections = Sections::parse(["-a", "-b"]);
+        assert_eq!(sections.section_name(0), "b");
+        assert_eq!(sections.segment_names(), ["-a", "b"]);
+    }
+
+    #[test]
+    fn section_named_after_last_segment() {
+        let sections = Sections::parse(["-a", "Boss", "Outro"]);
+        assert_eq!(sections.section_of(0), Some(0));
+        assert_eq!(sections.section_of(1), Some(0));
+        assert_eq!(sections.section_of(2), None);
+        assert_eq!(sections.section_name(0), "Boss");
+        assert_eq!(sections.name(0), "a");
+    }
+
+    #[test]
+    fn edits_follow_the_convention() {
+        let mut sections = Sections::parse(["a", "b", "c", "d"]);
+        sections.create(0, 2, String::from("World"));
+        assert_eq!(sections.segment_names(), ["-a", "-b", "{World}c", "d"]);
+
+        sections.outdent(1);
+        assert_eq!(sections.segment_names(), ["{World}a", "b", "{World}c", "d"]);
+
+        sections.indent(1);
+        sections.rename(1, String::from("c"));
+        assert_eq!(sections.segment_names(), ["{World}a", "-b", "c", "d"]);
+
+        sections.dissolve(1);
+        assert_eq!(sections.segment_names(), ["{World}a", "b", "c", "d"]);
+    }
+}
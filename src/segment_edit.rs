@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+
+use livesplit_core::{run::SegmentHistory, Run, Segment, Time};
+
+use crate::timing::{fastest, TIMING_METHODS};
+
+/// Merges the segment with the one after it. The merged segment keeps the
+/// name and the split times of the second one, as that's where the split
+/// happens. Its history combines the segment times of both for every attempt
+/// that finished them, and the best segment is the fastest of those.
+pub fn merge_with_next(run: &mut Run, index: usize) -> bool {
+    if index + 1 >= run.len() {
+        return false;
+    }
+
+    let first = run.segments_mut().remove(index);
+    let second = &mut run.segments_mut()[index];
+    let first_history: BTreeMap<i32, Time> = first.segment_history().iter().copied().collect();
+
+    let mut history = SegmentHistory::default();
+    for &(id, time) in second.segment_history().iter() {
+        let mut merged = Time::new();
+        for timing_method in TIMING_METHODS {
+            let first_time = first_history
+                .get(&id)
+                .and_then(|first| first[timing_method]);
+            merged[timing_method] = match first_time {
+                Some(first_time) => time[timing_method].map(|time| first_time + time),
+                // If the first split was skipped, the time of the second
+                // segment already covers both.
+                None => time[timing_method],
+            };
+        }
+        history.insert(id, merged);
+    }
+
+    for timing_method in TIMING_METHODS {
+        let fastest = fastest(history.iter().filter_map(|(_, time)| time[timing_method]));
+        let sum_of_best = || {
+            Some(
+                first.best_segment_time()[timing_method]?
+                    + second.best_segment_time()[timing_method]?,
+            )
+        };
+        let best_segment_time = fastest.or_else(sum_of_best);
+        second.best_segment_time_mut()[timing_method] = best_segment_time;
+    }
+    *second.segment_history_mut() = history;
+
+    run.mark_as_modified();
+    true
+}
+
+/// Splits the segment in two by inserting a new segment in front of it. The
+/// new segment has no times, and its history marks it as skipped in every
+/// attempt that reached the existing segment, so the times of the existing
+/// segment still cover both halves until the new split gets used.
+pub fn split(run: &mut Run, index: usize) -> bool {
+    if index >= run.len() {
+        return false;
+    }
+    let mut segment = Segment::new("");
+    for &(id, _) in run.segments()[index].segment_history().iter() {
+        segment.segment_history_mut().insert(id, Time::default());
+    }
+    run.segments_mut().insert(index, segment);
+    run.mark_as_modified();
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use livesplit_core::TimeSpan;
+
+    use super::*;
+
+    fn real_time(seconds: Option<f64>) -> Time {
+        Time::new().with_real_time(seconds.map(TimeSpan::from_seconds))
+    }
+
+    fn segment(name: &str, best: Option<f64>, history: &[(i32, Option<f64>)]) -> Segment {
+        let mut segment = Segment::new(name);
+        segment.set_best_segment_time(real_time(best));
+        for &(id, time) in history {
+            segment.segment_history_mut().insert(id, real_time(time));
+        }
+        segment
+    }
+
+    fn run(segments: impl IntoIterator<Item = Segment>) -> Run {
+        let mut run = Run::new();
+        for segment in segments {
+            run.push_segment(segment);
+        }
+        run
+    }
+
+    fn history(segment: &Segment) -> Vec<(i32, Option<TimeSpan>)> {
+        segment
+            .segment_history()
+            .iter()
+            .map(|&(id, time)| (id, time.real_time))
+            .collect()
+    }
+
+    fn seconds(seconds: f64) -> Option<TimeSpan> {
+        Some(TimeSpan::from_seconds(seconds))
+    }
+
+    #[test]
+    fn merge_combines_history() {
+        let mut run = run([
+            segment(
+                "First",
+                Some(10.0),
+                &[(1, Some(10.0)), (2, None), (3, Some(12.0))],
+            ),
+            segment(
+                "Second",
+                Some(20.0),
+                &[(1, Some(20.0)), (2, Some(35.0)), (3, None)],
+            ),
+            segment("Third", Some(5.0), &[(1, Some(5.0))]),
+        ]);
+
+        assert!(merge_with_next(&mut run, 0));
+        assert_eq!(run.len(), 2);
+
+        let merged = &run.segments()[0];
+        assert_eq!(merged.name(), "Second");
+        // The first split was skipped in the second attempt, so its time is
+        // already part of the second segment. The third attempt skipped the
+        // second split, so there's no time for the merged segment.
+        assert_eq!(
+            history(merged),
+            [(1, seconds(30.0)), (2, seconds(35.0)), (3, None)]
+        );
+        assert_eq!(merged.best_segment_time().real_time, seconds(30.0));
+        assert_eq!(run.segments()[1].name(), "Third");
+    }
+
+    #[test]
+    fn merge_without_history_sums_best_segments() {
+        let mut run = run([
+            segment("First", Some(10.0), &[]),
+            segment("Second", Some(20.0), &[]),
+        ]);
+        assert!(merge_with_next(&mut run, 0));
+        let merged = &run.segments()[0];
+        assert_eq!(merged.best_segment_time().real_time, seconds(30.0));
+        assert_eq!(merged.best_segment_time().game_time, None);
+    }
+
+    #[test]
+    fn merge_with_empty_best_segments() {
+        let mut run = run([
+            segment("First", None, &[]),
+            segment("Second", Some(20.0), &[]),
+        ]);
+        assert!(merge_with_next(&mut run, 0));
+        assert_eq!(run.segments()[0].best_segment_time().real_time, None);
+    }
+
+    #[test]
+    fn merge_last_segment_fails() {
+        let mut run = run([segment("Only", None, &[])]);
+        assert!(!merge_with_next(&mut run, 0));
+        assert_eq!(run.len(), 1);
+    }
+
+    #[test]
+    fn split_marks_new_segment_as_skipped() {
+        let mut run = run([
+            segment("First", Some(10.0), &[(1, Some(10.0)), (2, Some(11.0))]),
+            segment("Second", Some(20.0), &[(1, Some(20.0)), (2, None)]),
+        ]);
+
+        assert!(split(&mut run, 1));
+        assert_eq!(run.len(), 3);
+
+        let new = &run.segments()[1];
+        assert_eq!(new.name(), "");
+        assert_eq!(new.best_segment_time(), Time::default());
+        assert_eq!(history(new), [(1, None), (2, None)]);
+        assert_eq!(history(&run.segments()[2]), [(1, seconds(20.0)), (2, None)]);
+    }
+
+    #[test]
+    fn split_out_of_range_fails() {
+        let mut run = run([segment("Only", None, &[])]);
+        assert!(!split(&mut run, 1));
+        assert_eq!(run.len(), 1);
+    }
+}
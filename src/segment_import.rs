@@ -0,0 +1,206 @@
+use livesplit_core::TimeSpan;
+
+/// What a column after the segment names contains.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Column {
+    Ignored,
+    SplitTime,
+    SegmentTime,
+    BestSegment,
+}
+
+impl Column {
+    pub const NAMES: &'static [&'static str] =
+        &["Ignored", "Split Time", "Segment Time", "Best Segment"];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => Column::SplitTime,
+            2 => Column::SegmentTime,
+            3 => Column::BestSegment,
+            _ => Column::Ignored,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ImportedSegment {
+    pub name: String,
+    pub split_time: Option<TimeSpan>,
+    pub segment_time: Option<TimeSpan>,
+    pub best_segment_time: Option<TimeSpan>,
+}
+
+/// Parses one segment per line. The columns are separated by tabs, or
+/// otherwise by commas, with quoting like in the CSV export. The first column
+/// is the name of the segment, and empty or missing columns leave the time
+/// empty.
+pub fn parse(
+    text: &str,
+    columns: &[Column],
+    has_header: bool,
+) -> Result<Vec<ImportedSegment>, String> {
+    let mut segments = Vec::new();
+    let lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .skip(has_header as usize);
+
+    for (line_index, line) in lines {
+        let mut fields = fields(line).into_iter();
+        let mut segment = ImportedSegment {
+            name: fields.next().unwrap_or_default().trim().to_owned(),
+            ..Default::default()
+        };
+        for (&column, field) in columns.iter().zip(fields) {
+            let field = field.trim();
+            if column == Column::Ignored || field.is_empty() {
+                continue;
+            }
+            let time = field.parse::<TimeSpan>().map_err(|_| {
+                format!("Line {}: \"{field}\" is not a valid time.", line_index + 1)
+            })?;
+            match column {
+                Column::SplitTime => segment.split_time = Some(time),
+                Column::SegmentTime => segment.segment_time = Some(time),
+                Column::BestSegment => segment.best_segment_time = Some(time),
+                Column::Ignored => {}
+            }
+        }
+        segments.push(segment);
+    }
+
+    Ok(segments)
+}
+
+/// Turns the segment times into split times counting from the start, for the
+/// segments without a split time. The count continues from the split times
+/// that are there.
+pub fn accumulate_segment_times(segments: &mut [ImportedSegment]) {
+    let mut split_time = TimeSpan::zero();
+    for segment in segments {
+        if let Some(time) = segment.split_time {
+            split_time = time;
+        } else if let Some(time) = segment.segment_time {
+            split_time += time;
+            segment.split_time = Some(split_time);
+        }
+    }
+}
+
+fn fields(line: &str) -> Vec<String> {
+    if line.contains('\t') {
+        return line.split('\t').map(str::to_owned).collect();
+    }
+
+    let mut fields = vec![String::new()];
+    let mut is_quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().unwrap();
+        match c {
+            '"' if is_quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if is_quoted || field.trim().is_empty() => is_quoted = !is_quoted,
+            ',' if !is_quoted => fields.push(String::new()),
+            c => field.push(c),
+        }
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seconds(seconds: f64) -> Option<TimeSpan> {
+        Some(TimeSpan::from_seconds(seconds))
+    }
+
+    fn names(segments: &[ImportedSegment]) -> Vec<&str> {
+        segments.iter().map(|segment| &*segment.name).collect()
+    }
+
+    #[test]
+    fn splits_quoted_fields() {
+        assert_eq!(
+            fields(r#""Boss, Phase 1",1:00,"Say ""Hi""""#),
+            ["Boss, Phase 1", "1:00", r#"Say "Hi""#]
+        );
+        // Quotes within a field are kept.
+        assert_eq!(fields(r#"The "Tower",5"#), [r#"The "Tower""#, "5"]);
+    }
+
+    #[test]
+    fn prefers_tabs_over_commas() {
+        assert_eq!(fields("Boss, Phase 1\t1:00"), ["Boss, Phase 1", "1:00"]);
+        assert_eq!(fields("Boss,1:00"), ["Boss", "1:00"]);
+    }
+
+    #[test]
+    fn leaves_empty_and_missing_columns_empty() {
+        let columns = [Column::SplitTime, Column::SegmentTime, Column::BestSegment];
+        let segments = parse("First,,5\nSecond,1:00", &columns, false).unwrap();
+
+        assert_eq!(names(&segments), ["First", "Second"]);
+        assert_eq!(segments[0].split_time, None);
+        assert_eq!(segments[0].segment_time, seconds(5.0));
+        assert_eq!(segments[0].best_segment_time, None);
+        assert_eq!(segments[1].split_time, seconds(60.0));
+        assert_eq!(segments[1].segment_time, None);
+    }
+
+    #[test]
+    fn ignores_columns() {
+        let segments = parse(
+            "First,not a time,5",
+            &[Column::Ignored, Column::BestSegment],
+            false,
+        )
+        .unwrap();
+        assert_eq!(segments[0].best_segment_time, seconds(5.0));
+    }
+
+    #[test]
+    fn skips_header_after_blank_lines() {
+        let text = "\n  \nName,Time\nFirst,1\n\nSecond,2\n";
+        let segments = parse(text, &[Column::SplitTime], true).unwrap();
+        assert_eq!(names(&segments), ["First", "Second"]);
+        assert_eq!(segments[1].split_time, seconds(2.0));
+    }
+
+    #[test]
+    fn reports_the_line_of_invalid_times() {
+        let error = parse("First,1\n\nSecond,soon", &[Column::SplitTime], false).err();
+        assert_eq!(
+            error.as_deref(),
+            Some(r#"Line 3: "soon" is not a valid time."#)
+        );
+    }
+
+    #[test]
+    fn accumulates_segment_times_from_split_times() {
+        let mut segments = parse(
+            "First,,10\nSecond,,20\nThird,1:00,\nFourth,,5\nFifth,,",
+            &[Column::SplitTime, Column::SegmentTime],
+            false,
+        )
+        .unwrap();
+        accumulate_segment_times(&mut segments);
+
+        let split_times: Vec<_> = segments.iter().map(|segment| segment.split_time).collect();
+        assert_eq!(
+            split_times,
+            [
+                seconds(10.0),
+                seconds(30.0),
+                seconds(60.0),
+                seconds(65.0),
+                None
+            ]
+        );
+    }
+}
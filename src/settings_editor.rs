@@ -1,97 +1,693 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+};
 
+use anyhow::{bail, Result};
 use druid::{
     commands,
-    widget::{Button, Flex, ListIter, Scroll},
-    Data, Widget, WidgetExt,
-};
-use livesplit_core::{
-    settings::{SettingsDescription, Value},
-    HotkeyConfig,
+    lens::Identity,
+    theme,
+    widget::{
+        Button, Controller, Flex, Label, LineBreaking, Scroll, Switch, TextBox, ViewSwitcher,
+    },
+    Color, Data, Env, Event, EventCtx, FileDialogOptions, FileSpec, Lens, LensExt, Selector,
+    TextAlignment, Widget, WidgetExt,
 };
+use livesplit_core::{hotkey::Hotkey, HotkeyConfig, Timer, TimingMethod};
 
 use crate::{
-    consts::{BUTTON_SPACING, DIALOG_BUTTON_HEIGHT, DIALOG_BUTTON_WIDTH, MARGIN},
-    settings_table::{self, SettingsRow},
+    app_hotkeys::{Action, HotkeyMode},
+    combo_box,
+    config::{show_error, Config, Log},
+    consts::{
+        switch_style, BUTTON_ACTIVE_BOTTOM, BUTTON_ACTIVE_TOP, BUTTON_BORDER, BUTTON_SPACING,
+        COLUMN_LABEL_FONT, DIALOG_BUTTON_HEIGHT, DIALOG_BUTTON_WIDTH, GRID_BORDER, MARGIN,
+    },
+    formatter_scope::formatted,
+    hotkey_button,
+    safeguards::Safeguards,
+    striped_row::StripedRow,
+    timing::{timing_method_from_index, timing_method_index, TIMING_METHOD_NAMES},
 };
 
-#[derive(Clone, Data)]
+const HOTKEY_MODES: &[&str] = &["Global", "Focused Window Only", "Both"];
+const LOG_LEVELS: &[&str] = &["Off", "Error", "Warning", "Info", "Debug", "Trace"];
+const MIN_WINDOW_SIZE: f64 = 50.0;
+
+const SELECT_LAYOUT: Selector<druid::FileInfo> = Selector::new("settings-editor-select-layout");
+#[cfg(feature = "auto-splitting")]
+const SELECT_AUTO_SPLITTER: Selector<druid::FileInfo> =
+    Selector::new("settings-editor-select-auto-splitter");
+
+const CONFLICT_COLOR: Color = Color::rgb8(0xd0, 0x30, 0x30);
+
+#[derive(Copy, Clone, PartialEq, Data)]
+enum Tab {
+    General,
+    Window,
+    Hotkeys,
+    Safeguards,
+    Logging,
+    #[cfg(feature = "auto-splitting")]
+    AutoSplitter,
+}
+
+#[derive(Clone, Data, Lens)]
 pub struct State {
-    state: Rc<SettingsDescription>,
+    tab: Tab,
+    general: GeneralSettings,
+    window: WindowSettings,
     #[data(ignore)]
-    pub editor: Rc<RefCell<Option<HotkeyConfig>>>,
+    hotkey_config: HotkeyConfig,
+    hotkeys: Rc<[Binding]>,
+    hotkey_mode: usize,
+    safeguards: SafeguardSettings,
+    log: LogSettings,
+    auto_splitter: String,
     #[data(ignore)]
     pub closed_with_ok: bool,
 }
 
+#[derive(Clone, Data, Lens)]
+struct GeneralSettings {
+    timing_method: usize,
+    comparison: usize,
+    #[data(ignore)]
+    comparisons: Arc<[Arc<str>]>,
+    layout: String,
+}
+
+#[derive(Clone, Data, Lens)]
+struct WindowSettings {
+    width: f64,
+    height: f64,
+}
+
+#[derive(Clone, Data, Lens)]
+struct SafeguardSettings {
+    split_cooldown: u64,
+    reset_twice_within: u64,
+    confirm_reset_ahead_of_pb: bool,
+}
+
+#[derive(Clone, Data, Lens)]
+struct LogSettings {
+    enable: bool,
+    level: usize,
+    clear: bool,
+}
+
 impl State {
-    pub(crate) fn new(editor: HotkeyConfig) -> Self {
+    pub(crate) fn new(config: &Config, timer: &Timer) -> Self {
+        let hotkey_config = config.hotkeys();
+        let comparisons: Arc<[Arc<str>]> = timer.run().comparisons().map(Into::into).collect();
+        let comparison = comparisons
+            .iter()
+            .position(|c| &**c == timer.current_comparison())
+            .unwrap_or_default();
+        let (width, height) = config.window_size();
+        let log = config.log();
+        let safeguards = config.safeguards();
+
         Self {
-            state: Rc::new(editor.settings_description()),
-            editor: Rc::new(RefCell::new(Some(editor))),
+            tab: Tab::General,
+            general: GeneralSettings {
+                timing_method: timing_method_index(timer.current_timing_method()),
+                comparison,
+                comparisons,
+                layout: path_to_string(config.layout_path()),
+            },
+            window: WindowSettings { width, height },
+            hotkey_config,
+            hotkeys: bindings(
+                &hotkey_config,
+                &config.app_hotkey_primaries(),
+                config.alternate_hotkeys(),
+            ),
+            hotkey_mode: match config.hotkey_mode() {
+                HotkeyMode::Global => 0,
+                HotkeyMode::FocusedOnly => 1,
+                HotkeyMode::Both => 2,
+            },
+            safeguards: SafeguardSettings {
+                split_cooldown: safeguards.split_cooldown,
+                reset_twice_within: safeguards.reset_twice_within,
+                confirm_reset_ahead_of_pb: safeguards.confirm_reset_ahead_of_pb,
+            },
+            log: LogSettings {
+                enable: log.enable,
+                level: log.level.unwrap_or(log::LevelFilter::Warn) as usize,
+                clear: log.clear,
+            },
+            auto_splitter: path_to_string(config.global_auto_splitter()),
             closed_with_ok: false,
         }
     }
-}
 
-impl ListIter<SettingsRow> for State {
-    fn for_each(&self, mut cb: impl FnMut(&SettingsRow, usize)) {
-        let mut row = SettingsRow {
-            index: 0,
-            text: String::new(),
-            value: Value::Bool(false),
-        };
+    fn validate(&self) -> Result<()> {
+        if let Some(path) = self.layout() {
+            if !path.is_file() {
+                bail!("The layout \"{}\" doesn't exist.", path.display());
+            }
+        }
+        if let Some(path) = self.auto_splitter() {
+            if !path.is_file() {
+                bail!("The auto splitter \"{}\" doesn't exist.", path.display());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn timing_method(&self) -> TimingMethod {
+        timing_method_from_index(self.general.timing_method)
+    }
+
+    pub fn comparison(&self) -> &str {
+        &self.general.comparisons[self.general.comparison]
+    }
+
+    pub fn layout(&self) -> Option<PathBuf> {
+        string_to_path(&self.general.layout)
+    }
+
+    pub fn window_size(&self) -> (f64, f64) {
+        (self.window.width, self.window.height)
+    }
 
-        for (index, field) in self.state.fields.iter().enumerate() {
-            row.index = index;
-            row.text.clone_from(&field.text);
-            row.value.clone_from(&field.value);
-            cb(&row, index);
+    pub fn safeguards(&self) -> Safeguards {
+        Safeguards {
+            split_cooldown: self.safeguards.split_cooldown,
+            reset_twice_within: self.safeguards.reset_twice_within,
+            confirm_reset_ahead_of_pb: self.safeguards.confirm_reset_ahead_of_pb,
         }
     }
 
-    fn for_each_mut(&mut self, mut cb: impl FnMut(&mut SettingsRow, usize)) {
-        let mut row = SettingsRow {
-            index: 0,
-            text: String::new(),
-            value: Value::Bool(false),
-        };
-
-        let mut editor = self.editor.borrow_mut();
-        let editor = editor.as_mut().unwrap();
-        let mut changed = false;
-
-        for (index, field) in self.state.fields.iter().enumerate() {
-            row.index = index;
-            row.text.clone_from(&field.text);
-            row.value.clone_from(&field.value);
-            cb(&mut row, index);
-            if row.value != field.value {
-                editor.set_value(index, row.value.clone());
-                changed = true;
-            }
+    pub fn log(&self) -> Log {
+        Log {
+            enable: self.log.enable,
+            level: Some(match self.log.level {
+                0 => log::LevelFilter::Off,
+                1 => log::LevelFilter::Error,
+                2 => log::LevelFilter::Warn,
+                3 => log::LevelFilter::Info,
+                4 => log::LevelFilter::Debug,
+                _ => log::LevelFilter::Trace,
+            }),
+            clear: self.log.clear,
         }
+    }
+
+    pub fn auto_splitter(&self) -> Option<PathBuf> {
+        string_to_path(&self.auto_splitter)
+    }
 
-        if changed {
-            self.state = Rc::new(editor.settings_description());
+    pub fn hotkey_config(&self) -> HotkeyConfig {
+        let mut config = self.hotkey_config;
+        for (action, binding) in Action::ALL.into_iter().zip(self.hotkeys.iter()) {
+            action.set_hotkey(&mut config, binding.primary.0);
         }
+        config
+    }
+
+    /// The hotkeys of the actions that the hotkey config doesn't cover.
+    pub fn app_hotkey_primaries(&self) -> BTreeMap<Action, Hotkey> {
+        Action::ALL
+            .into_iter()
+            .zip(self.hotkeys.iter())
+            .filter(|(action, _)| !action.is_in_hotkey_config())
+            .filter_map(|(action, binding)| Some((action, binding.primary.0?)))
+            .collect()
+    }
+
+    pub fn alternate_hotkeys(&self) -> BTreeMap<Action, Hotkey> {
+        Action::ALL
+            .into_iter()
+            .zip(self.hotkeys.iter())
+            .filter_map(|(action, binding)| Some((action, binding.alternate.0?)))
+            .collect()
+    }
+
+    pub fn hotkey_mode(&self) -> HotkeyMode {
+        match self.hotkey_mode {
+            0 => HotkeyMode::Global,
+            1 => HotkeyMode::FocusedOnly,
+            _ => HotkeyMode::Both,
+        }
+    }
+}
+
+#[derive(Clone, Data, Lens)]
+struct Binding {
+    primary: hotkey_button::Hotkey,
+    alternate: hotkey_button::Hotkey,
+    primary_conflict: bool,
+    alternate_conflict: bool,
+}
+
+fn bindings(
+    config: &HotkeyConfig,
+    app_primaries: &BTreeMap<Action, Hotkey>,
+    alternates: &BTreeMap<Action, Hotkey>,
+) -> Rc<[Binding]> {
+    let mut bindings: Vec<Binding> = Action::ALL
+        .into_iter()
+        .map(|action| Binding {
+            primary: hotkey_button::Hotkey(if action.is_in_hotkey_config() {
+                action.hotkey(config)
+            } else {
+                app_primaries.get(&action).copied()
+            }),
+            alternate: hotkey_button::Hotkey(alternates.get(&action).copied()),
+            primary_conflict: false,
+            alternate_conflict: false,
+        })
+        .collect();
+    mark_conflicts(&mut bindings);
+    bindings.into()
+}
+
+/// Marks all the hotkeys that are bound more than once, as only one of the
+/// actions would end up being triggered.
+fn mark_conflicts(bindings: &mut [Binding]) {
+    let hotkeys: Vec<Hotkey> = bindings
+        .iter()
+        .flat_map(|binding| [binding.primary.0, binding.alternate.0])
+        .flatten()
+        .collect();
+    let is_bound_twice = |hotkey: Option<Hotkey>| {
+        hotkey.map_or(false, |hotkey| {
+            hotkeys.iter().filter(|&&other| other == hotkey).count() > 1
+        })
+    };
+    for binding in bindings {
+        binding.primary_conflict = is_bound_twice(binding.primary.0);
+        binding.alternate_conflict = is_bound_twice(binding.alternate.0);
     }
+}
+
+fn path_to_string(path: Option<&Path>) -> String {
+    path.map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn string_to_path(path: &str) -> Option<PathBuf> {
+    let path = path.trim();
+    (!path.is_empty()).then(|| PathBuf::from(path))
+}
 
-    fn data_len(&self) -> usize {
-        self.state.fields.len()
+struct FileSelection;
+
+impl<W: Widget<State>> Controller<State, W> for FileSelection {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut State,
+        env: &Env,
+    ) {
+        if let Event::Command(command) = event {
+            if let Some(file_info) = command.get(SELECT_LAYOUT) {
+                data.general.layout = file_info.path().to_string_lossy().into_owned();
+                ctx.set_handled();
+                return;
+            }
+            #[cfg(feature = "auto-splitting")]
+            if let Some(file_info) = command.get(SELECT_AUTO_SPLITTER) {
+                data.auto_splitter = file_info.path().to_string_lossy().into_owned();
+                ctx.set_handled();
+                return;
+            }
+        }
+        child.event(ctx, event, data, env)
     }
 }
 
 pub fn root_widget() -> impl Widget<State> {
     Flex::column()
+        .with_child(tabs())
         .with_flex_child(settings_editor(), 1.0)
         .with_child(dialog_buttons())
+        .controller(FileSelection)
+}
+
+fn tab(name: &'static str, tab: Tab) -> impl Widget<State> {
+    Button::new(name)
+        .on_click(move |_, state: &mut State, _| {
+            state.tab = tab;
+        })
+        .env_scope(move |env, state: &State| {
+            if state.tab == tab {
+                env.set(theme::BUTTON_LIGHT, BUTTON_ACTIVE_TOP);
+                env.set(theme::BUTTON_DARK, BUTTON_ACTIVE_BOTTOM);
+            }
+        })
+}
+
+fn tabs() -> impl Widget<State> {
+    let tabs = Flex::row()
+        .with_child(tab("General", Tab::General))
+        .with_child(tab("Window", Tab::Window))
+        .with_child(tab("Hotkeys", Tab::Hotkeys))
+        .with_child(tab("Safeguards", Tab::Safeguards))
+        .with_child(tab("Logging", Tab::Logging));
+
+    #[cfg(feature = "auto-splitting")]
+    let tabs = tabs.with_child(tab("Auto Splitter", Tab::AutoSplitter));
+
+    tabs.env_scope(|env, _| {
+        env.set(theme::BUTTON_BORDER_RADIUS, 0.0);
+    })
+    .padding((MARGIN, MARGIN, MARGIN, 0.0))
+    .expand_width()
 }
 
 fn settings_editor() -> impl Widget<State> {
-    Scroll::new(settings_table::widget().padding(MARGIN))
-        .vertical()
-        .expand_height()
+    Scroll::new(
+        ViewSwitcher::new(
+            |state: &State, _| state.tab,
+            |tab, state: &State, _| match tab {
+                Tab::General => Box::new(general(state.general.comparisons.clone())),
+                Tab::Window => Box::new(window()),
+                Tab::Hotkeys => Box::new(hotkeys()),
+                Tab::Safeguards => Box::new(safeguards()),
+                Tab::Logging => Box::new(logging()),
+                #[cfg(feature = "auto-splitting")]
+                Tab::AutoSplitter => Box::new(auto_splitter()),
+            },
+        )
+        .padding(MARGIN),
+    )
+    .vertical()
+    .expand_height()
+}
+
+fn setting<T: Data>(
+    index: usize,
+    text: &'static str,
+    widget: impl Widget<T> + 'static,
+) -> impl Widget<T> {
+    StripedRow::at(
+        index,
+        Flex::row()
+            .with_spacer(6.0)
+            .with_child(
+                Label::new(text)
+                    .with_line_break_mode(LineBreaking::WordWrap)
+                    .fix_width(225.0),
+            )
+            .with_spacer(GRID_BORDER)
+            .with_flex_child(widget, 1.0)
+            .with_spacer(6.0)
+            .padding(4.0),
+    )
+}
+
+fn note<T: Data>(index: usize, text: &'static str) -> impl Widget<T> {
+    setting(
+        index,
+        "Note",
+        Label::new(text)
+            .with_line_break_mode(LineBreaking::WordWrap)
+            .expand_width(),
+    )
+}
+
+fn path_selection(
+    placeholder: &'static str,
+    select: impl Fn(&mut EventCtx) + 'static,
+) -> impl Widget<String> {
+    Flex::row()
+        .with_flex_child(
+            TextBox::new().with_placeholder(placeholder).expand_width(),
+            1.0,
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_child(Button::new("Browse...").on_click(move |ctx, _, _| select(ctx)))
+        .with_spacer(BUTTON_SPACING)
+        .with_child(Button::new("❌").on_click(|_, path: &mut String, _| {
+            path.clear();
+        }))
+}
+
+fn general(comparisons: Arc<[Arc<str>]>) -> impl Widget<State> {
+    Flex::column()
+        .with_child(setting(
+            0,
+            "Timing Method",
+            combo_box::static_list(TIMING_METHOD_NAMES).lens(GeneralSettings::timing_method),
+        ))
+        .with_child(setting(
+            1,
+            "Comparison",
+            combo_box::dynamic_list(comparisons).lens(GeneralSettings::comparison),
+        ))
+        .with_child(setting(
+            2,
+            "Layout",
+            path_selection("Uses the default layout", |ctx| {
+                ctx.submit_command(
+                    commands::SHOW_OPEN_PANEL.with(
+                        FileDialogOptions::new()
+                            .title("Select Layout")
+                            .allowed_types(vec![
+                                FileSpec {
+                                    name: "LiveSplit Layouts",
+                                    extensions: &["lsl", "ls1l"],
+                                },
+                                FileSpec {
+                                    name: "All Files",
+                                    extensions: &["*.*"],
+                                },
+                            ])
+                            .accept_command(SELECT_LAYOUT),
+                    ),
+                );
+            })
+            .lens(GeneralSettings::layout),
+        ))
+        .border(BUTTON_BORDER, 1.0)
+        .lens(State::general)
+}
+
+fn window_dimension() -> impl Widget<f64> {
+    formatted(
+        TextBox::new().with_text_alignment(TextAlignment::End),
+        |buf, val| {
+            use std::fmt::Write;
+            let _ = write!(buf, "{}", val);
+        },
+        |val| val.parse().ok().filter(|&val: &f64| val >= MIN_WINDOW_SIZE),
+    )
+}
+
+fn window() -> impl Widget<State> {
+    Flex::column()
+        .with_child(setting(
+            0,
+            "Width",
+            window_dimension().lens(WindowSettings::width),
+        ))
+        .with_child(setting(
+            1,
+            "Height",
+            window_dimension().lens(WindowSettings::height),
+        ))
+        .with_child(note(
+            2,
+            "The window size is applied the next time LiveSplit One starts.",
+        ))
+        .border(BUTTON_BORDER, 1.0)
+        .lens(State::window)
+}
+
+fn hotkey_binding(
+    hotkey: impl Lens<Binding, hotkey_button::Hotkey> + 'static,
+    conflict: fn(&Binding) -> bool,
+) -> impl Widget<Binding> {
+    hotkey_button::widget()
+        .lens(hotkey)
+        .env_scope(move |env, binding: &Binding| {
+            if conflict(binding) {
+                env.set(theme::BORDER_DARK, CONFLICT_COLOR);
+                env.set(theme::BORDER_LIGHT, CONFLICT_COLOR);
+                env.set(theme::BUTTON_BORDER_WIDTH, 2.0);
+            }
+        })
+}
+
+fn hotkeys() -> impl Widget<State> {
+    let mut column = Flex::column().with_child(setting(
+        0,
+        "Action",
+        Flex::row()
+            .with_flex_child(
+                Label::new("Hotkey")
+                    .with_font(COLUMN_LABEL_FONT)
+                    .expand_width(),
+                1.0,
+            )
+            .with_spacer(GRID_BORDER)
+            .with_flex_child(
+                Label::new("Alternate")
+                    .with_font(COLUMN_LABEL_FONT)
+                    .expand_width(),
+                1.0,
+            ),
+    ));
+
+    for (index, action) in Action::ALL.into_iter().enumerate() {
+        let bindings = Flex::row()
+            .with_flex_child(
+                hotkey_binding(Binding::primary, |binding| binding.primary_conflict),
+                1.0,
+            )
+            .with_spacer(GRID_BORDER)
+            .with_flex_child(
+                hotkey_binding(Binding::alternate, |binding| binding.alternate_conflict),
+                1.0,
+            )
+            .lens(Identity.map(
+                move |state: &State| state.hotkeys[index].clone(),
+                move |state: &mut State, binding: Binding| {
+                    if !state.hotkeys[index].same(&binding) {
+                        let mut hotkeys = state.hotkeys.to_vec();
+                        hotkeys[index] = binding;
+                        mark_conflicts(&mut hotkeys);
+                        state.hotkeys = hotkeys.into();
+                    }
+                },
+            ));
+        column = column.with_child(setting(index + 1, action.name(), bindings));
+    }
+
+    column
+        .with_child(setting(
+            Action::ALL.len() + 1,
+            "Hotkey Mode",
+            combo_box::static_list(HOTKEY_MODES).lens(State::hotkey_mode),
+        ))
+        .with_child(note(
+            Action::ALL.len() + 2,
+            "Hotkeys that are bound more than once are highlighted in red. Global hotkeys \
+            also work while the timer window isn't focused.",
+        ))
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            Button::new("Reset to Defaults")
+                .on_click(|_, state: &mut State, _| {
+                    state.hotkeys =
+                        bindings(&HotkeyConfig::default(), &BTreeMap::new(), &BTreeMap::new());
+                })
+                .align_right(),
+        )
+}
+
+fn milliseconds() -> impl Widget<u64> {
+    formatted(
+        TextBox::new().with_text_alignment(TextAlignment::End),
+        |buf, val| {
+            use std::fmt::Write;
+            let _ = write!(buf, "{} ms", val);
+        },
+        |val| val.trim().trim_end_matches("ms").trim_end().parse().ok(),
+    )
+}
+
+fn safeguards() -> impl Widget<State> {
+    Flex::column()
+        .with_child(setting(
+            0,
+            "Ignore Splits Within",
+            milliseconds().lens(SafeguardSettings::split_cooldown),
+        ))
+        .with_child(setting(
+            1,
+            "Reset Needs Two Presses Within",
+            milliseconds().lens(SafeguardSettings::reset_twice_within),
+        ))
+        .with_child(setting(
+            2,
+            "Confirm Reset When Ahead of PB",
+            Switch::new()
+                .env_scope(|env, _| switch_style(env))
+                .lens(SafeguardSettings::confirm_reset_ahead_of_pb)
+                .center(),
+        ))
+        .with_child(note(
+            3,
+            "Durations of 0 ms turn the safeguard off. The safeguards apply to the hotkeys and the context menu alike.",
+        ))
+        .border(BUTTON_BORDER, 1.0)
+        .lens(State::safeguards)
+}
+
+fn logging() -> impl Widget<State> {
+    Flex::column()
+        .with_child(setting(
+            0,
+            "Write Log File",
+            Switch::new()
+                .env_scope(|env, _| switch_style(env))
+                .lens(LogSettings::enable)
+                .center(),
+        ))
+        .with_child(setting(
+            1,
+            "Level",
+            combo_box::static_list(LOG_LEVELS).lens(LogSettings::level),
+        ))
+        .with_child(setting(
+            2,
+            "Clear Log File on Start",
+            Switch::new()
+                .env_scope(|env, _| switch_style(env))
+                .lens(LogSettings::clear)
+                .center(),
+        ))
+        .with_child(note(
+            3,
+            "Changes to the logging settings apply after restarting.",
+        ))
+        .border(BUTTON_BORDER, 1.0)
+        .lens(State::log)
+}
+
+#[cfg(feature = "auto-splitting")]
+fn auto_splitter() -> impl Widget<State> {
+    Flex::column()
+        .with_child(setting(
+            0,
+            "Global Auto Splitter",
+            path_selection("No auto splitter", |ctx| {
+                ctx.submit_command(
+                    commands::SHOW_OPEN_PANEL.with(
+                        FileDialogOptions::new()
+                            .title("Select Auto Splitter")
+                            .allowed_types(vec![
+                                FileSpec {
+                                    name: "Auto Splitters",
+                                    extensions: &["wasm"],
+                                },
+                                FileSpec {
+                                    name: "All Files",
+                                    extensions: &["*.*"],
+                                },
+                            ])
+                            .accept_command(SELECT_AUTO_SPLITTER),
+                    ),
+                );
+            })
+            .lens(State::auto_splitter),
+        ))
+        .with_child(note(
+            1,
+            "Splits that have their own auto splitter associated in the splits editor use that one instead.",
+        ))
+        .border(BUTTON_BORDER, 1.0)
 }
 
 fn dialog_buttons() -> impl Widget<State> {
@@ -100,6 +696,10 @@ fn dialog_buttons() -> impl Widget<State> {
         .with_child(
             Button::new("OK")
                 .on_click(|ctx, state: &mut State, _| {
+                    if let Err(e) = state.validate() {
+                        show_error(e);
+                        return;
+                    }
                     state.closed_with_ok = true;
                     ctx.submit_command(commands::CLOSE_WINDOW);
                 })
@@ -115,3 +715,55 @@ fn dialog_buttons() -> impl Widget<State> {
         )
         .padding(MARGIN)
 }
+
+#[cfg(test)]
+mod tests {
+    use livesplit_core::hotkey::{KeyCode, Modifiers};
+
+    use super::*;
+
+    fn hotkey(key_code: KeyCode) -> Hotkey {
+        key_code.with_modifiers(Modifiers::empty())
+    }
+
+    fn binding(bindings: &[Binding], action: Action) -> &Binding {
+        let index = Action::ALL.iter().position(|&a| a == action).unwrap();
+        &bindings[index]
+    }
+
+    #[test]
+    fn bindings_cover_every_action() {
+        let config = HotkeyConfig::default();
+        let app_primaries = BTreeMap::from([(Action::ToggleGlobalHotkeys, hotkey(KeyCode::F12))]);
+        let alternates = BTreeMap::from([(Action::NextComparison, hotkey(KeyCode::PageDown))]);
+        let bindings = bindings(&config, &app_primaries, &alternates);
+
+        assert_eq!(bindings.len(), Action::ALL.len());
+        let split = binding(&bindings, Action::Split);
+        assert_eq!(split.primary.0, config.split);
+        assert_eq!(split.alternate.0, None);
+
+        let next_comparison = binding(&bindings, Action::NextComparison);
+        assert_eq!(next_comparison.primary.0, config.next_comparison);
+        assert_eq!(next_comparison.alternate.0, Some(hotkey(KeyCode::PageDown)));
+
+        let toggle = binding(&bindings, Action::ToggleGlobalHotkeys);
+        assert_eq!(toggle.primary.0, Some(hotkey(KeyCode::F12)));
+        assert_eq!(binding(&bindings, Action::PreviousNotes).primary.0, None);
+        assert!(bindings
+            .iter()
+            .all(|binding| !binding.primary_conflict && !binding.alternate_conflict));
+    }
+
+    #[test]
+    fn conflicts_are_marked_on_both_bindings() {
+        let config = HotkeyConfig::default();
+        let alternates = BTreeMap::from([(Action::ToggleTimingMethod, config.split.unwrap())]);
+        let bindings = bindings(&config, &BTreeMap::new(), &alternates);
+
+        assert!(binding(&bindings, Action::Split).primary_conflict);
+        assert!(binding(&bindings, Action::ToggleTimingMethod).alternate_conflict);
+        assert!(!binding(&bindings, Action::ToggleTimingMethod).primary_conflict);
+        assert!(!binding(&bindings, Action::Reset).primary_conflict);
+    }
+}
@@ -0,0 +1,566 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs,
+    path::Path,
+    rc::Rc,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use druid::{
+    commands,
+    lens::Identity,
+    widget::{
+        Button, Controller, CrossAxisAlignment, Flex, Label, LineBreaking, List, ListIter, Scroll,
+        TextBox,
+    },
+    Data, Env, Event, EventCtx, FileDialogOptions, FileInfo, FileSpec, Lens, LensExt, Selector,
+    Target, Widget, WidgetExt,
+};
+use livesplit_core::{
+    analysis::sum_of_segments,
+    run::parser::composite,
+    timing::formatter::{Delta, Regular, SegmentTime, TimeFormatter},
+    Run, RunEditor, SharedTimer, TimeSpan, Timer, TimerPhase, TimingMethod,
+};
+
+use crate::{
+    combo_box,
+    config::show_error,
+    consts::{BUTTON_BORDER, BUTTON_SPACING, COLUMN_LABEL_FONT, GRID_BORDER, MARGIN},
+    refresh::Refresh,
+    striped_row::{Striped, StripedRow},
+    timing::{timing_method_from_index, timing_method_index, TIMING_METHODS, TIMING_METHOD_NAMES},
+};
+
+/// Asks the timer to add the personal best of the other splits as a custom
+/// comparison to the current splits.
+pub const IMPORT_COMPARISON: Selector<(String, Arc<Run>)> =
+    Selector::new("splits-comparison-import-comparison");
+const SELECT_OTHER_SPLITS: Selector<FileInfo> =
+    Selector::new("splits-comparison-select-other-splits");
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+const TIME_WIDTH: f64 = 90.0;
+
+#[derive(Clone, Data, Lens)]
+pub struct State {
+    #[data(ignore)]
+    timer: SharedTimer,
+    other: Option<Arc<Run>>,
+    other_path: String,
+    rows: Rc<Vec<Row>>,
+    sum_of_best: String,
+    timing_method: usize,
+    comparison_name: String,
+}
+
+#[derive(Clone, Data, PartialEq)]
+struct Row {
+    index: usize,
+    name: String,
+    split_time: String,
+    other_split_time: String,
+    split_difference: String,
+    best_segment: String,
+    other_best_segment: String,
+    best_segment_difference: String,
+}
+
+impl State {
+    pub fn new(timer: SharedTimer) -> Self {
+        let timing_method = timing_method_index(timer.read().unwrap().current_timing_method());
+        Self {
+            timer,
+            other: None,
+            other_path: String::new(),
+            rows: Rc::new(Vec::new()),
+            sum_of_best: String::new(),
+            timing_method,
+            comparison_name: String::new(),
+        }
+    }
+
+    fn timing_method(&self) -> TimingMethod {
+        timing_method_from_index(self.timing_method)
+    }
+
+    fn load_other(&mut self, path: &Path) -> Result<()> {
+        let file = fs::read(path).context("Failed reading the file.")?;
+        let other = composite::parse(&file, Some(path))
+            .context("Failed parsing the file.")?
+            .run;
+        self.comparison_name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.other_path = path.to_string_lossy().into_owned();
+        self.other = Some(Arc::new(other));
+        self.refresh();
+        Ok(())
+    }
+
+    /// Compares against the current splits, which may have changed since the
+    /// window was opened.
+    fn refresh(&mut self) {
+        let other = match &self.other {
+            Some(other) => other,
+            None => return,
+        };
+        let timer = self.timer.read().unwrap();
+        let run = timer.run();
+        let timing_method = self.timing_method();
+
+        let (pairs, unmatched) = align(run, other);
+        let mut rows = Vec::with_capacity(pairs.len() + unmatched.len());
+        for (index, other_index) in pairs.into_iter().enumerate() {
+            let segment = &run.segments()[index];
+            let other_segment = other_index.map(|index| &other.segments()[index]);
+            let split_time = segment.personal_best_split_time()[timing_method];
+            let other_split_time =
+                other_segment.and_then(|segment| segment.personal_best_split_time()[timing_method]);
+            let best_segment = segment.best_segment_time()[timing_method];
+            let other_best_segment =
+                other_segment.and_then(|segment| segment.best_segment_time()[timing_method]);
+            rows.push(Row {
+                index: rows.len(),
+                name: segment.name().to_owned(),
+                split_time: format_split_time(split_time),
+                other_split_time: format_split_time(other_split_time),
+                split_difference: format_difference(split_time, other_split_time),
+                best_segment: format_segment_time(best_segment),
+                other_best_segment: format_segment_time(other_best_segment),
+                best_segment_difference: format_difference(best_segment, other_best_segment),
+            });
+        }
+        for other_index in unmatched {
+            let segment = &other.segments()[other_index];
+            rows.push(Row {
+                index: rows.len(),
+                name: segment.name().to_owned(),
+                split_time: String::new(),
+                other_split_time: format_split_time(
+                    segment.personal_best_split_time()[timing_method],
+                ),
+                split_difference: String::new(),
+                best_segment: String::new(),
+                other_best_segment: format_segment_time(segment.best_segment_time()[timing_method]),
+                best_segment_difference: String::new(),
+            });
+        }
+
+        let sum_of_best =
+            sum_of_segments::calculate_best(run.segments(), false, false, timing_method);
+        let other_sum_of_best =
+            sum_of_segments::calculate_best(other.segments(), false, false, timing_method);
+        let sum_of_best = format!(
+            "Sum of Best: {} / {} ({})",
+            format_split_time(sum_of_best),
+            format_split_time(other_sum_of_best),
+            format_difference(sum_of_best, other_sum_of_best),
+        );
+
+        if rows != *self.rows {
+            self.rows = Rc::new(rows);
+        }
+        if sum_of_best != self.sum_of_best {
+            self.sum_of_best = sum_of_best;
+        }
+    }
+
+    fn can_import(&self) -> bool {
+        let name = self.comparison_name.trim();
+        self.other.is_some()
+            && !name.is_empty()
+            && !self
+                .timer
+                .read()
+                .unwrap()
+                .run()
+                .comparisons()
+                .any(|comparison| comparison == name)
+    }
+}
+
+fn format_split_time(time: Option<TimeSpan>) -> String {
+    time.map(|time| Regular::new().format(time).to_string())
+        .unwrap_or_default()
+}
+
+fn format_segment_time(time: Option<TimeSpan>) -> String {
+    time.map(|time| SegmentTime::new().format(time).to_string())
+        .unwrap_or_default()
+}
+
+/// How much slower the other splits are. A negative difference means they are
+/// faster.
+fn format_difference(time: Option<TimeSpan>, other_time: Option<TimeSpan>) -> String {
+    match (time, other_time) {
+        (Some(time), Some(other_time)) => Delta::new().format(other_time - time).to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Pairs up every segment with the segment of the same name in the other
+/// splits. If several segments share a name, they are paired up in the order
+/// they appear in. The segments of the other splits that aren't paired up are
+/// returned separately.
+fn align(run: &Run, other: &Run) -> (Vec<Option<usize>>, Vec<usize>) {
+    let mut by_name = BTreeMap::<&str, VecDeque<usize>>::new();
+    for (index, segment) in other.segments().iter().enumerate() {
+        by_name.entry(segment.name()).or_default().push_back(index);
+    }
+
+    let pairs = run
+        .segments()
+        .iter()
+        .map(|segment| by_name.get_mut(segment.name())?.pop_front())
+        .collect();
+
+    let mut unmatched: Vec<usize> = by_name.into_values().flatten().collect();
+    unmatched.sort_unstable();
+
+    (pairs, unmatched)
+}
+
+/// Adds the personal best of the other splits as a custom comparison to the
+/// splits of the timer. Segments without a counterpart in the other splits
+/// stay empty in the comparison.
+pub fn import_comparison(timer: &mut Timer, name: &str, other: &Run) -> Result<()> {
+    if timer.current_phase() != TimerPhase::NotRunning {
+        bail!("The comparison can't be imported while the timer is running.");
+    }
+
+    let (pairs, _) = align(timer.run(), other);
+    let mut editor = RunEditor::new(timer.run().clone()).context("Failed editing the splits.")?;
+    editor
+        .add_comparison(name)
+        .context("The comparison can't be added.")?;
+
+    for timing_method in TIMING_METHODS {
+        editor.select_timing_method(timing_method);
+        for (index, &other_index) in pairs.iter().enumerate() {
+            let time = other_index.and_then(|other_index| {
+                other.segments()[other_index].personal_best_split_time()[timing_method]
+            });
+            editor.select_only(index);
+            editor.active_segment().set_comparison_time(name, time);
+        }
+    }
+
+    timer
+        .set_run(editor.close())
+        .ok()
+        .context("The splits can't be used with the timer.")
+}
+
+impl ListIter<Row> for State {
+    fn for_each(&self, mut cb: impl FnMut(&Row, usize)) {
+        for (index, row) in self.rows.iter().enumerate() {
+            cb(row, index);
+        }
+    }
+
+    fn for_each_mut(&mut self, mut cb: impl FnMut(&mut Row, usize)) {
+        for (index, row) in self.rows.iter().enumerate() {
+            cb(&mut row.clone(), index);
+        }
+    }
+
+    fn data_len(&self) -> usize {
+        self.rows.len()
+    }
+}
+
+impl Striped for Row {
+    fn index(&self) -> usize {
+        self.index
+    }
+}
+
+fn columns<T: Data>(name: impl Widget<T> + 'static, times: [Box<dyn Widget<T>>; 6]) -> Flex<T> {
+    let mut row = Flex::row()
+        .with_spacer(6.0)
+        .with_flex_child(name.expand_width(), 1.0);
+    for (index, time) in times.into_iter().enumerate() {
+        // The split times and the best segments are spaced apart.
+        row.add_spacer(if index == 3 {
+            3.0 * GRID_BORDER
+        } else {
+            GRID_BORDER
+        });
+        row.add_child(time.align_right().fix_width(TIME_WIDTH));
+    }
+    row.with_spacer(6.0)
+}
+
+fn row() -> impl Widget<Row> {
+    let time = |get: fn(&Row) -> &String| -> Box<dyn Widget<Row>> {
+        Box::new(Label::new(move |row: &Row, _: &_| get(row).clone()))
+    };
+    StripedRow::new(
+        columns(
+            Label::new(|row: &Row, _: &_| row.name.clone())
+                .with_line_break_mode(LineBreaking::Clip),
+            [
+                time(|row| &row.split_time),
+                time(|row| &row.other_split_time),
+                time(|row| &row.split_difference),
+                time(|row| &row.best_segment),
+                time(|row| &row.other_best_segment),
+                time(|row| &row.best_segment_difference),
+            ],
+        )
+        .padding(2.0),
+    )
+}
+
+fn header() -> impl Widget<State> {
+    let column = |name| -> Box<dyn Widget<State>> {
+        Box::new(Label::new(name).with_font(COLUMN_LABEL_FONT))
+    };
+    Flex::column()
+        .with_child(columns(
+            Label::new(""),
+            [
+                column("PB Split"),
+                column(""),
+                column(""),
+                column("Best Segment"),
+                column(""),
+                column(""),
+            ],
+        ))
+        .with_child(columns(
+            Label::new("Segment").with_font(COLUMN_LABEL_FONT),
+            [
+                column("Current"),
+                column("Other"),
+                column("Difference"),
+                column("Current"),
+                column("Other"),
+                column("Difference"),
+            ],
+        ))
+}
+
+fn toolbar() -> impl Widget<State> {
+    Flex::row()
+        .with_flex_child(
+            Label::new(|state: &State, _: &_| {
+                if state.other_path.is_empty() {
+                    String::from("Open the splits to compare with.")
+                } else {
+                    state.other_path.clone()
+                }
+            })
+            .with_line_break_mode(LineBreaking::Clip)
+            .expand_width(),
+            1.0,
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_child(Button::new("Open...").on_click(|ctx, _, _| {
+            ctx.submit_command(
+                commands::SHOW_OPEN_PANEL.with(
+                    FileDialogOptions::new()
+                        .title("Compare With Splits")
+                        .allowed_types(vec![
+                            FileSpec {
+                                name: "LiveSplit Splits",
+                                extensions: &["lss"],
+                            },
+                            FileSpec {
+                                name: "All Files",
+                                extensions: &["*.*"],
+                            },
+                        ])
+                        .accept_command(SELECT_OTHER_SPLITS),
+                ),
+            );
+        }))
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            combo_box::static_list(TIMING_METHOD_NAMES)
+                .lens(Identity.map(
+                    |state: &State| state.timing_method,
+                    |state: &mut State, timing_method: usize| {
+                        if state.timing_method != timing_method {
+                            state.timing_method = timing_method;
+                            state.refresh();
+                        }
+                    },
+                ))
+                .fix_width(120.0),
+        )
+}
+
+fn footer() -> impl Widget<State> {
+    Flex::row()
+        .with_child(Label::new(|state: &State, _: &_| state.sum_of_best.clone()))
+        .with_flex_spacer(1.0)
+        .with_child(
+            TextBox::new()
+                .with_placeholder("Comparison Name")
+                .lens(State::comparison_name)
+                .fix_width(200.0),
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            Button::new("Import PB as Comparison")
+                .on_click(|ctx, state: &mut State, _| {
+                    if let Some(other) = &state.other {
+                        ctx.submit_command(
+                            IMPORT_COMPARISON
+                                .with((state.comparison_name.trim().to_owned(), other.clone()))
+                                .to(Target::Global),
+                        );
+                    }
+                })
+                .disabled_if(|state: &State, _| !state.can_import()),
+        )
+}
+
+struct SplitsSelection;
+
+impl<W: Widget<State>> Controller<State, W> for SplitsSelection {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut State,
+        env: &Env,
+    ) {
+        if let Event::Command(command) = event {
+            if let Some(file_info) = command.get(SELECT_OTHER_SPLITS) {
+                if let Err(e) = data.load_other(file_info.path()) {
+                    show_error(e);
+                }
+                ctx.set_handled();
+                return;
+            }
+        }
+        child.event(ctx, event, data, env)
+    }
+}
+
+pub fn root_widget() -> impl Widget<State> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(toolbar())
+        .with_spacer(BUTTON_SPACING)
+        .with_child(header())
+        .with_spacer(GRID_BORDER)
+        .with_flex_child(
+            Scroll::new(List::new(row).border(BUTTON_BORDER, 1.0))
+                .vertical()
+                .expand_height(),
+            1.0,
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_child(footer())
+        .padding(MARGIN)
+        .controller(SplitsSelection)
+        .controller(Refresh::new(REFRESH_INTERVAL, State::refresh))
+}
+
+#[cfg(test)]
+mod tests {
+    use livesplit_core::{Segment, Time};
+
+    use super::*;
+
+    fn seconds(seconds: f64) -> Option<TimeSpan> {
+        Some(TimeSpan::from_seconds(seconds))
+    }
+
+    /// Splits with the given segments. The personal best split times are
+    /// given in seconds as the real time and the game time.
+    fn run(segments: &[(&str, f64, f64)]) -> Run {
+        let mut run = Run::new();
+        for &(name, real_time, game_time) in segments {
+            let mut segment = Segment::new(name);
+            segment.set_personal_best_split_time(
+                Time::new()
+                    .with_real_time(seconds(real_time))
+                    .with_game_time(seconds(game_time)),
+            );
+            run.push_segment(segment);
+        }
+        run
+    }
+
+    fn names(names: &[&str]) -> Run {
+        run(&names
+            .iter()
+            .map(|&name| (name, 0.0, 0.0))
+            .collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn aligns_duplicate_names_in_order() {
+        let (pairs, unmatched) = align(
+            &names(&["A", "B", "A", "C"]),
+            &names(&["A", "X", "A", "B", "A"]),
+        );
+        assert_eq!(pairs, [Some(0), Some(3), Some(2), None]);
+        assert_eq!(unmatched, [1, 4]);
+    }
+
+    #[test]
+    fn aligns_without_common_names() {
+        let (pairs, unmatched) = align(&names(&["A", "B"]), &names(&["C"]));
+        assert_eq!(pairs, [None, None]);
+        assert_eq!(unmatched, [0]);
+    }
+
+    #[test]
+    fn imports_both_timing_methods() {
+        let mut timer = Timer::new(run(&[
+            ("A", 10.0, 8.0),
+            ("B", 20.0, 17.0),
+            ("C", 30.0, 25.0),
+        ]))
+        .unwrap();
+        let other = run(&[("A", 11.0, 9.0), ("C", 33.0, 28.0), ("D", 40.0, 35.0)]);
+
+        import_comparison(&mut timer, "Other", &other).unwrap();
+
+        let times: Vec<_> = timer
+            .run()
+            .segments()
+            .iter()
+            .map(|segment| {
+                let time = segment.comparison("Other");
+                (time.real_time, time.game_time)
+            })
+            .collect();
+        assert_eq!(
+            times,
+            [
+                (seconds(11.0), seconds(9.0)),
+                (None, None),
+                (seconds(33.0), seconds(28.0)),
+            ]
+        );
+        // The personal best is left alone.
+        assert_eq!(
+            timer.run().segments()[2].personal_best_split_time().real_time,
+            seconds(30.0)
+        );
+    }
+
+    #[test]
+    fn refuses_existing_comparisons_and_running_timers() {
+        let mut timer = Timer::new(run(&[("A", 10.0, 8.0)])).unwrap();
+        let other = run(&[("A", 11.0, 9.0)]);
+
+        import_comparison(&mut timer, "Other", &other).unwrap();
+        assert!(import_comparison(&mut timer, "Other", &other).is_err());
+
+        timer.start();
+        assert!(import_comparison(&mut timer, "Another", &other).is_err());
+        assert!(!timer.run().comparisons().any(|name| name == "Another"));
+    }
+}
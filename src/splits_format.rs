@@ -0,0 +1,383 @@
+use anyhow::{Context, Result};
+use livesplit_core::{
+    comparison::best_split_times,
+    run::{parser::TimerKind, saver::livesplit::save_timer},
+    timing::formatter::{Complete, TimeFormatter},
+    Run, TimeSpan, Timer,
+};
+use serde_json::json;
+
+/// The splits formats that can be written back to. Splits in any other format
+/// need to be saved as LiveSplit splits. That includes the generic formats, as
+/// the parser doesn't tell which of them the splits were stored in.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum SplitsFormat {
+    LiveSplit,
+    Urn,
+    WSplit,
+    SplitterZ,
+}
+
+/// The comparison that WSplit stores along with the personal best.
+const WSPLIT_OLD_RUN: &str = "Old Run";
+
+impl SplitsFormat {
+    pub fn detect(kind: &TimerKind<'_>) -> Option<Self> {
+        Some(match kind {
+            TimerKind::LiveSplit => SplitsFormat::LiveSplit,
+            TimerKind::Urn => SplitsFormat::Urn,
+            TimerKind::WSplit => SplitsFormat::WSplit,
+            TimerKind::SplitterZ => SplitsFormat::SplitterZ,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SplitsFormat::LiveSplit => "LiveSplit",
+            SplitsFormat::Urn => "Urn",
+            SplitsFormat::WSplit => "WSplit",
+            SplitsFormat::SplitterZ => "SplitterZ",
+        }
+    }
+
+    pub fn write(self, timer: &Timer) -> Result<String> {
+        Ok(match self {
+            SplitsFormat::LiveSplit => {
+                let mut buf = String::new();
+                save_timer(timer, &mut buf).context("Failed saving the splits.")?;
+                buf
+            }
+            SplitsFormat::Urn => urn(timer.run()),
+            SplitsFormat::WSplit => wsplit(timer.run()),
+            SplitsFormat::SplitterZ => splitterz(timer.run()),
+        })
+    }
+
+    /// Lists the information in the run that gets lost when saving it in this
+    /// format.
+    pub fn unsupported_data(self, run: &Run) -> Vec<&'static str> {
+        let mut unsupported = Vec::new();
+        if self == SplitsFormat::LiveSplit {
+            return unsupported;
+        }
+
+        // Urn only has a title, which is the game. The others only have a
+        // title for the category.
+        if self == SplitsFormat::Urn {
+            if !run.category_name().is_empty() {
+                unsupported.push("Category");
+            }
+        } else if !run.game_name().is_empty() {
+            unsupported.push("Game Name");
+        }
+
+        let offset = run.offset();
+        let keeps_offset = match self {
+            // Urn can only delay the start.
+            SplitsFormat::Urn => offset <= TimeSpan::zero(),
+            SplitsFormat::SplitterZ => offset == TimeSpan::zero(),
+            _ => true,
+        };
+        if !keeps_offset {
+            unsupported.push("Start Timer At");
+        }
+
+        // None of them know about game time.
+        if run.segments().iter().any(|segment| {
+            segment.personal_best_split_time().game_time.is_some()
+                || segment.best_segment_time().game_time.is_some()
+        }) {
+            unsupported.push("Game Time");
+        }
+
+        if self == SplitsFormat::WSplit
+            && run
+                .segments()
+                .iter()
+                .any(|segment| segment.name().contains(','))
+        {
+            unsupported.push("Commas in Segment Names");
+        }
+
+        if !run.game_icon().is_empty() || run.segments().iter().any(|s| !s.icon().is_empty()) {
+            unsupported.push("Icons");
+        }
+
+        if !run.attempt_history().is_empty()
+            || run
+                .segments()
+                .iter()
+                .any(|segment| segment.segment_history().iter().next().is_some())
+        {
+            unsupported.push("Attempt History");
+        }
+
+        if !run
+            .custom_comparisons()
+            .iter()
+            .all(|c| c == "Personal Best" || (self == SplitsFormat::WSplit && c == WSPLIT_OLD_RUN))
+        {
+            unsupported.push("Custom Comparisons");
+        }
+
+        let metadata = run.metadata();
+        if !metadata.run_id().is_empty()
+            || !metadata.platform_name().is_empty()
+            || !metadata.region_name().is_empty()
+            || metadata.custom_variables().next().is_some()
+        {
+            unsupported.push("Metadata");
+        }
+
+        unsupported
+    }
+}
+
+fn urn_time(time: Option<TimeSpan>) -> String {
+    // Urn uses 0 for a missing time.
+    time.map_or_else(
+        || String::from("0"),
+        |time| Complete::new().format(time).to_string(),
+    )
+}
+
+fn urn(run: &Run) -> String {
+    let splits: Vec<_> = run
+        .segments()
+        .iter()
+        .map(|segment| {
+            let split_time = segment.personal_best_split_time().real_time;
+            // The personal best is the best split time there is without any
+            // history.
+            let best_split_time = segment
+                .comparison(best_split_times::NAME)
+                .real_time
+                .or(split_time);
+            json!({
+                "title": segment.name(),
+                "time": urn_time(split_time),
+                "best_time": urn_time(best_split_time),
+                "best_segment": urn_time(segment.best_segment_time().real_time),
+            })
+        })
+        .collect();
+
+    // A negative offset delays the start, while Urn can't start at a positive
+    // time, so that is dropped.
+    let start_delay = (TimeSpan::zero() - run.offset()).max(TimeSpan::zero());
+
+    let document = json!({
+        "title": run.game_name(),
+        "attempt_count": run.attempt_count(),
+        "start_delay": urn_time(Some(start_delay)),
+        "splits": splits,
+    });
+
+    serde_json::to_string_pretty(&document).unwrap_or_default()
+}
+
+/// WSplit stores the times in seconds, with 0 for a missing time.
+fn wsplit_time(time: Option<TimeSpan>) -> String {
+    time.map_or(0.0, |time| time.total_seconds()).to_string()
+}
+
+fn wsplit(run: &Run) -> String {
+    // The offset is how long the start is delayed, in milliseconds.
+    let mut text = format!(
+        "Title={}\nAttempts={}\nOffset={}\nSize=152,25\n",
+        run.category_name(),
+        run.attempt_count(),
+        -run.offset().total_milliseconds(),
+    );
+    let has_old_run = run
+        .custom_comparisons()
+        .iter()
+        .any(|comparison| comparison == WSPLIT_OLD_RUN);
+    for segment in run.segments() {
+        let old_time = has_old_run
+            .then(|| segment.comparison(WSPLIT_OLD_RUN).real_time)
+            .flatten();
+        text.push_str(&format!(
+            "{},{},{},{}\n",
+            // The columns are separated by commas without any escaping.
+            segment.name().replace(',', ""),
+            wsplit_time(old_time),
+            wsplit_time(segment.personal_best_split_time().real_time),
+            wsplit_time(segment.best_segment_time().real_time),
+        ));
+    }
+    text
+}
+
+/// SplitterZ stores 0 for a missing time.
+fn splitterz_time(time: Option<TimeSpan>) -> String {
+    Complete::new()
+        .format(time.unwrap_or_else(TimeSpan::zero))
+        .to_string()
+}
+
+/// SplitterZ separates the columns by commas and escapes them as `‡`.
+fn splitterz_text(text: &str) -> String {
+    text.replace(',', "‡")
+}
+
+fn splitterz(run: &Run) -> String {
+    let mut text = format!(
+        "{},{}\n",
+        splitterz_text(run.category_name()),
+        run.attempt_count()
+    );
+    for segment in run.segments() {
+        text.push_str(&format!(
+            "{},{},{}\n",
+            splitterz_text(segment.name()),
+            splitterz_time(segment.personal_best_split_time().real_time),
+            splitterz_time(segment.best_segment_time().real_time),
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use livesplit_core::{run::parser::composite, Segment, Time};
+    use serde_json::Value;
+
+    use super::*;
+
+    fn run_with_offset(seconds: f64) -> Run {
+        let mut run = Run::new();
+        run.push_segment(Segment::new("Split"));
+        run.set_offset(TimeSpan::from_seconds(seconds));
+        run
+    }
+
+    fn start_delay(run: &Run) -> Value {
+        let document: Value = serde_json::from_str(&urn(run)).unwrap();
+        document["start_delay"].clone()
+    }
+
+    #[test]
+    fn urn_start_delay_is_never_negative() {
+        assert_eq!(start_delay(&run_with_offset(-5.0)), "00:00:05.0000000");
+        assert_eq!(start_delay(&run_with_offset(0.0)), "00:00:00.0000000");
+        assert_eq!(start_delay(&run_with_offset(5.0)), "00:00:00.0000000");
+    }
+
+    #[test]
+    fn urn_reports_positive_offset_as_unsupported() {
+        let unsupported = |seconds| SplitsFormat::Urn.unsupported_data(&run_with_offset(seconds));
+        assert!(unsupported(5.0).contains(&"Start Timer At"));
+        assert!(!unsupported(-5.0).contains(&"Start Timer At"));
+        assert!(SplitsFormat::LiveSplit
+            .unsupported_data(&run_with_offset(5.0))
+            .is_empty());
+    }
+
+    /// Splits with a personal best of A at 10 seconds, a skipped B and C at 30
+    /// seconds.
+    fn run() -> Run {
+        let mut run = Run::new();
+        run.set_category_name("Any%, No Clips");
+        run.set_attempt_count(5);
+        run.set_offset(TimeSpan::from_seconds(-2.0));
+        for (name, split_time, best_segment) in [
+            ("A", Some(10.0), 9.0),
+            ("B", None, 8.0),
+            ("C, the End", Some(30.0), 9.5),
+        ] {
+            let mut segment = Segment::new(name);
+            segment.personal_best_split_time_mut().real_time =
+                split_time.map(TimeSpan::from_seconds);
+            segment.best_segment_time_mut().real_time = Some(TimeSpan::from_seconds(best_segment));
+            run.push_segment(segment);
+        }
+        run
+    }
+
+    fn parse(text: &str) -> (Run, Option<SplitsFormat>) {
+        let parsed = composite::parse(text.as_bytes(), None).unwrap();
+        (parsed.run, SplitsFormat::detect(&parsed.kind))
+    }
+
+    fn real_times(run: &Run, f: impl Fn(&Segment) -> Time) -> Vec<Option<TimeSpan>> {
+        run.segments()
+            .iter()
+            .map(|segment| f(segment).real_time)
+            .collect()
+    }
+
+    fn seconds(seconds: &[Option<f64>]) -> Vec<Option<TimeSpan>> {
+        seconds
+            .iter()
+            .map(|seconds| seconds.map(TimeSpan::from_seconds))
+            .collect()
+    }
+
+    #[test]
+    fn wsplit_round_trip() {
+        let (parsed, format) = parse(&wsplit(&run()));
+
+        assert!(format == Some(SplitsFormat::WSplit));
+        assert_eq!(parsed.category_name(), "Any%, No Clips");
+        assert_eq!(parsed.attempt_count(), 5);
+        assert_eq!(parsed.offset(), TimeSpan::from_seconds(-2.0));
+        let names: Vec<_> = parsed.segments().iter().map(Segment::name).collect();
+        assert_eq!(names, ["A", "B", "C the End"]);
+        assert_eq!(
+            real_times(&parsed, |s| s.personal_best_split_time()),
+            seconds(&[Some(10.0), None, Some(30.0)])
+        );
+        assert_eq!(
+            real_times(&parsed, |s| s.best_segment_time()),
+            seconds(&[Some(9.0), Some(8.0), Some(9.5)])
+        );
+    }
+
+    #[test]
+    fn splitterz_round_trip() {
+        let (parsed, format) = parse(&splitterz(&run()));
+
+        assert!(format == Some(SplitsFormat::SplitterZ));
+        assert_eq!(parsed.category_name(), "Any%, No Clips");
+        assert_eq!(parsed.attempt_count(), 5);
+        let names: Vec<_> = parsed.segments().iter().map(Segment::name).collect();
+        assert_eq!(names, ["A", "B", "C, the End"]);
+        assert_eq!(
+            real_times(&parsed, |s| s.personal_best_split_time()),
+            seconds(&[Some(10.0), None, Some(30.0)])
+        );
+        assert_eq!(
+            real_times(&parsed, |s| s.best_segment_time()),
+            seconds(&[Some(9.0), Some(8.0), Some(9.5)])
+        );
+    }
+
+    #[test]
+    fn urn_writes_best_split_times() {
+        let document: Value = serde_json::from_str(&urn(&run())).unwrap();
+        let best_times: Vec<_> = document["splits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|split| split["best_time"].as_str().unwrap().to_owned())
+            .collect();
+        assert_eq!(best_times, ["00:00:10.0000000", "0", "00:00:30.0000000"]);
+    }
+
+    #[test]
+    fn reports_what_each_format_loses() {
+        let mut run = run();
+        run.set_game_name("Game");
+        let loses = |format: SplitsFormat, data| format.unsupported_data(&run).contains(&data);
+
+        assert!(loses(SplitsFormat::WSplit, "Commas in Segment Names"));
+        assert!(loses(SplitsFormat::WSplit, "Game Name"));
+        assert!(!loses(SplitsFormat::WSplit, "Start Timer At"));
+        assert!(loses(SplitsFormat::SplitterZ, "Start Timer At"));
+        assert!(!loses(SplitsFormat::SplitterZ, "Commas in Segment Names"));
+        assert!(loses(SplitsFormat::Urn, "Category"));
+        assert!(!loses(SplitsFormat::Urn, "Icons"));
+    }
+}
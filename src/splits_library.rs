@@ -0,0 +1,537 @@
+use std::{
+    cell::RefCell,
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use druid::{
+    commands,
+    lens::Identity,
+    widget::{
+        Button, Controller, CrossAxisAlignment, Flex, Label, LineBreaking, List, ListIter, Scroll,
+        TextBox,
+    },
+    Data, Env, Event, EventCtx, FileDialogOptions, FileInfo, LensExt, Selector, Target, Widget,
+    WidgetExt,
+};
+use livesplit_core::{
+    run::parser::composite,
+    timing::formatter::{Regular, TimeFormatter},
+    TimeSpan,
+};
+
+use crate::{
+    combo_box,
+    config::Config,
+    consts::{BUTTON_BORDER, BUTTON_SPACING, COLUMN_LABEL_FONT, GRID_BORDER, MARGIN},
+    refresh::Refresh,
+    striped_row::{Striped, StripedRow},
+};
+
+/// Asks the timer to open the splits, going through the usual checks for
+/// unsaved changes.
+pub const OPEN_SPLITS: Selector<Arc<Path>> = Selector::new("splits-library-open-splits");
+const SELECT_FOLDER: Selector<FileInfo> = Selector::new("splits-library-select-folder");
+
+const SORT_ORDERS: &[&str] = &[
+    "Game",
+    "Category",
+    "Attempts",
+    "Personal Best",
+    "Last Modified",
+];
+const MAX_SCAN_DEPTH: usize = 8;
+const REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The entries of the files that were already parsed. They are parsed again
+/// once the file gets modified.
+static CACHE: Mutex<BTreeMap<Arc<Path>, Entry>> = Mutex::new(BTreeMap::new());
+
+#[derive(Clone)]
+struct Entry {
+    path: Arc<Path>,
+    game: String,
+    category: String,
+    attempts: u32,
+    personal_best: Option<TimeSpan>,
+    modified: Option<SystemTime>,
+}
+
+#[derive(Clone, Data)]
+pub struct State {
+    #[data(ignore)]
+    config: Rc<RefCell<Config>>,
+    entries: Rc<Vec<Entry>>,
+    visible: Rc<Vec<usize>>,
+    search: String,
+    sort: usize,
+    loading: bool,
+    /// Where the background thread puts the entries once it's done.
+    #[data(ignore)]
+    loaded: Arc<Mutex<Option<Vec<Entry>>>>,
+}
+
+impl State {
+    pub fn new(config: Rc<RefCell<Config>>) -> Self {
+        let mut state = Self {
+            config,
+            entries: Rc::new(Vec::new()),
+            visible: Rc::new(Vec::new()),
+            search: String::new(),
+            sort: 4,
+            loading: false,
+            loaded: Default::default(),
+        };
+        state.reload();
+        state
+    }
+
+    /// Collects every known splits file: the ones that were opened before
+    /// and the pinned ones, as well as the ones in the folders that were
+    /// scanned. Scanning the folders and parsing the files happens in the
+    /// background.
+    fn reload(&mut self) {
+        let mut paths = BTreeSet::<Arc<Path>>::new();
+        let folders: Vec<PathBuf>;
+        {
+            let config = self.config.borrow();
+            // The history is still included for the splits that were opened
+            // before the library kept track of them.
+            for categories in config.splits_history().values() {
+                for category in categories.values() {
+                    paths.extend(category.iter().cloned());
+                }
+            }
+            paths.extend(config.library_splits().iter().cloned());
+            paths.extend(config.pinned_splits().iter().cloned());
+            folders = config.library_folders().iter().cloned().collect();
+        }
+
+        // A reload that is still running delivers its entries to the
+        // previous slot, so they are ignored.
+        let loaded = Arc::new(Mutex::new(None));
+        self.loaded = loaded.clone();
+        self.loading = true;
+
+        let result = thread::Builder::new()
+            .name("Splits Library".into())
+            .spawn(move || {
+                for folder in &folders {
+                    scan(folder, 0, &mut paths);
+                }
+                let entries = paths.into_iter().filter_map(load_entry).collect();
+                *loaded.lock().unwrap() = Some(entries);
+            });
+        if let Err(e) = result {
+            log::error!("Failed to start loading the splits library: {e}");
+            self.loading = false;
+        }
+    }
+
+    /// Takes over the entries once they are loaded.
+    fn refresh(&mut self) {
+        let entries = self.loaded.lock().unwrap().take();
+        if let Some(entries) = entries {
+            self.entries = Rc::new(entries);
+            self.loading = false;
+            self.update_visible();
+        }
+    }
+
+    fn update_visible(&mut self) {
+        let search = self.search.trim().to_lowercase();
+        let entries = &self.entries;
+        let mut visible: Vec<usize> = (0..entries.len())
+            .filter(|&index| {
+                let entry = &entries[index];
+                search.is_empty()
+                    || entry.game.to_lowercase().contains(&search)
+                    || entry.category.to_lowercase().contains(&search)
+                    || entry
+                        .path
+                        .to_string_lossy()
+                        .to_lowercase()
+                        .contains(&search)
+            })
+            .collect();
+
+        match self.sort {
+            0 => visible.sort_by(|&a, &b| {
+                let (a, b) = (&entries[a], &entries[b]);
+                (&a.game, &a.category).cmp(&(&b.game, &b.category))
+            }),
+            1 => visible.sort_by(|&a, &b| {
+                let (a, b) = (&entries[a], &entries[b]);
+                (&a.category, &a.game).cmp(&(&b.category, &b.game))
+            }),
+            2 => visible.sort_by_key(|&index| Reverse(entries[index].attempts)),
+            // Runs without a personal best go last.
+            3 => visible.sort_by_key(|&index| {
+                let personal_best = entries[index].personal_best;
+                (personal_best.is_none(), personal_best)
+            }),
+            _ => visible.sort_by_key(|&index| Reverse(entries[index].modified)),
+        }
+
+        self.visible = Rc::new(visible);
+    }
+}
+
+fn scan(folder: &Path, depth: usize, paths: &mut BTreeSet<Arc<Path>>) {
+    let read_dir = match fs::read_dir(folder) {
+        Ok(read_dir) => read_dir,
+        Err(e) => {
+            log::warn!("Failed to scan {}: {e}", folder.display());
+            return;
+        }
+    };
+
+    for entry in read_dir.flatten() {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => {
+                if depth < MAX_SCAN_DEPTH {
+                    scan(&path, depth + 1, paths);
+                }
+            }
+            Ok(_) => {
+                let is_splits = path
+                    .extension()
+                    .map_or(false, |extension| extension.eq_ignore_ascii_case("lss"));
+                if is_splits {
+                    paths.insert(path.into());
+                }
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+fn load_entry(path: Arc<Path>) -> Option<Entry> {
+    let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+    if let Some(entry) = CACHE.lock().unwrap().get(&path) {
+        if modified.is_some() && entry.modified == modified {
+            return Some(entry.clone());
+        }
+    }
+
+    let file = fs::read(&path).ok()?;
+    let run = composite::parse(&file, Some(&path)).ok()?.run;
+    let personal_best = run.segments().last().and_then(|segment| {
+        let time = segment.personal_best_split_time();
+        time.real_time.or(time.game_time)
+    });
+
+    let entry = Entry {
+        game: run.game_name().to_owned(),
+        category: run.extended_category_name(false, false, true).to_string(),
+        attempts: run.attempt_count(),
+        personal_best,
+        modified,
+        path,
+    };
+    CACHE
+        .lock()
+        .unwrap()
+        .insert(entry.path.clone(), entry.clone());
+    Some(entry)
+}
+
+#[derive(Clone, Data)]
+struct Row {
+    index: usize,
+    path: Arc<Path>,
+    game: String,
+    category: String,
+    attempts: String,
+    personal_best: String,
+    modified: String,
+}
+
+impl ListIter<Row> for State {
+    fn for_each(&self, mut cb: impl FnMut(&Row, usize)) {
+        for (index, &entry) in self.visible.iter().enumerate() {
+            let entry = &self.entries[entry];
+            let row = Row {
+                index,
+                path: entry.path.clone(),
+                game: entry.game.clone(),
+                category: entry.category.clone(),
+                attempts: entry.attempts.to_string(),
+                personal_best: entry
+                    .personal_best
+                    .map(|time| Regular::new().format(time).to_string())
+                    .unwrap_or_default(),
+                modified: entry
+                    .modified
+                    .map(|modified| {
+                        chrono::DateTime::<chrono::Local>::from(modified)
+                            .format("%Y-%m-%d %H:%M")
+                            .to_string()
+                    })
+                    .unwrap_or_default(),
+            };
+            cb(&row, index);
+        }
+    }
+
+    fn for_each_mut(&mut self, mut cb: impl FnMut(&mut Row, usize)) {
+        self.for_each(|row, index| cb(&mut row.clone(), index))
+    }
+
+    fn data_len(&self) -> usize {
+        self.visible.len()
+    }
+}
+
+impl Striped for Row {
+    fn index(&self) -> usize {
+        self.index
+    }
+}
+
+const GAME_WIDTH: f64 = 180.0;
+const ATTEMPTS_WIDTH: f64 = 70.0;
+const PERSONAL_BEST_WIDTH: f64 = 100.0;
+const MODIFIED_WIDTH: f64 = 130.0;
+const OPEN_WIDTH: f64 = 60.0;
+
+fn columns<T: Data>(
+    game: impl Widget<T> + 'static,
+    category: impl Widget<T> + 'static,
+    attempts: impl Widget<T> + 'static,
+    personal_best: impl Widget<T> + 'static,
+    modified: impl Widget<T> + 'static,
+    open: impl Widget<T> + 'static,
+) -> Flex<T> {
+    Flex::row()
+        .with_spacer(6.0)
+        .with_child(game.fix_width(GAME_WIDTH))
+        .with_spacer(GRID_BORDER)
+        .with_flex_child(category.expand_width(), 1.0)
+        .with_spacer(GRID_BORDER)
+        .with_child(attempts.fix_width(ATTEMPTS_WIDTH))
+        .with_spacer(GRID_BORDER)
+        .with_child(personal_best.fix_width(PERSONAL_BEST_WIDTH))
+        .with_spacer(GRID_BORDER)
+        .with_child(modified.fix_width(MODIFIED_WIDTH))
+        .with_spacer(GRID_BORDER)
+        .with_child(open.fix_width(OPEN_WIDTH))
+        .with_spacer(6.0)
+}
+
+fn row() -> impl Widget<Row> {
+    StripedRow::new(
+        columns(
+            Label::new(|row: &Row, _: &_| row.game.clone())
+                .with_line_break_mode(LineBreaking::WordWrap),
+            Label::new(|row: &Row, _: &_| row.category.clone())
+                .with_line_break_mode(LineBreaking::WordWrap),
+            Label::new(|row: &Row, _: &_| row.attempts.clone()),
+            Label::new(|row: &Row, _: &_| row.personal_best.clone()),
+            Label::new(|row: &Row, _: &_| row.modified.clone()),
+            Button::new("Open").on_click(|ctx, row: &mut Row, _| {
+                ctx.submit_command(OPEN_SPLITS.with(row.path.clone()).to(Target::Global));
+            }),
+        )
+        .padding(2.0),
+    )
+}
+
+fn header() -> impl Widget<State> {
+    let column = |name| Label::new(name).with_font(COLUMN_LABEL_FONT);
+    columns(
+        column("Game"),
+        column("Category"),
+        column("Attempts"),
+        column("Personal Best"),
+        column("Last Modified"),
+        Label::new(""),
+    )
+}
+
+fn toolbar() -> impl Widget<State> {
+    Flex::row()
+        .with_flex_child(
+            TextBox::new()
+                .with_placeholder("Search")
+                .lens(Identity.map(
+                    |state: &State| state.search.clone(),
+                    |state: &mut State, search: String| {
+                        if state.search != search {
+                            state.search = search;
+                            state.update_visible();
+                        }
+                    },
+                ))
+                .expand_width(),
+            1.0,
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_child(Label::new("Sort By"))
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            combo_box::static_list(SORT_ORDERS)
+                .lens(Identity.map(
+                    |state: &State| state.sort,
+                    |state: &mut State, sort: usize| {
+                        if state.sort != sort {
+                            state.sort = sort;
+                            state.update_visible();
+                        }
+                    },
+                ))
+                .fix_width(140.0),
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_child(Button::new("Scan Folder...").on_click(|ctx, _, _| {
+            ctx.submit_command(
+                commands::SHOW_OPEN_PANEL.with(
+                    FileDialogOptions::new()
+                        .title("Scan Folder for Splits")
+                        .select_directories()
+                        .accept_command(SELECT_FOLDER),
+                ),
+            );
+        }))
+        .with_spacer(BUTTON_SPACING)
+        .with_child(
+            Button::new("Refresh")
+                .on_click(|_, state: &mut State, _| state.reload())
+                .disabled_if(|state: &State, _| state.loading),
+        )
+}
+
+struct FolderSelection;
+
+impl<W: Widget<State>> Controller<State, W> for FolderSelection {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut State,
+        env: &Env,
+    ) {
+        if let Event::Command(command) = event {
+            if let Some(file_info) = command.get(SELECT_FOLDER) {
+                data.config
+                    .borrow_mut()
+                    .add_library_folder(file_info.path().to_path_buf());
+                data.reload();
+                ctx.set_handled();
+                return;
+            }
+        }
+        child.event(ctx, event, data, env)
+    }
+}
+
+pub fn root_widget() -> impl Widget<State> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(toolbar())
+        .with_spacer(BUTTON_SPACING)
+        .with_child(header())
+        .with_spacer(GRID_BORDER)
+        .with_flex_child(
+            Scroll::new(List::new(row).border(BUTTON_BORDER, 1.0))
+                .vertical()
+                .expand_height(),
+            1.0,
+        )
+        .with_spacer(BUTTON_SPACING)
+        .with_child(Label::new(|state: &State, _: &_| {
+            if state.loading {
+                String::from("Loading...")
+            } else {
+                format!("{} splits", state.entries.len())
+            }
+        }))
+        .padding(MARGIN)
+        .controller(FolderSelection)
+        .controller(Refresh::new(REFRESH_INTERVAL, State::refresh))
+}
+
+#[cfg(test)]
+mod tests {
+    use livesplit_core::{run::saver::livesplit::save_timer, Run, Segment, Timer};
+
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "livesplit-one-splits-library-{}-{name}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_splits(path: &Path, game: &str) {
+        let mut run = Run::new();
+        run.set_game_name(game);
+        run.set_category_name("Any%");
+        run.push_segment(Segment::new("Split"));
+        let timer = Timer::new(run).unwrap();
+        let mut buf = String::new();
+        save_timer(&timer, &mut buf).unwrap();
+        fs::write(path, buf).unwrap();
+    }
+
+    fn set_modified(path: &Path, modified: SystemTime) {
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[test]
+    fn reuses_entries_until_modified() {
+        let dir = test_dir("cache");
+        let path: Arc<Path> = dir.join("Splits.lss").into();
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+
+        write_splits(&path, "First");
+        set_modified(&path, modified);
+        assert_eq!(load_entry(path.clone()).unwrap().game, "First");
+
+        // The file isn't parsed again as long as it has the same time.
+        write_splits(&path, "Second");
+        set_modified(&path, modified);
+        assert_eq!(load_entry(path.clone()).unwrap().game, "First");
+
+        set_modified(&path, modified + Duration::from_secs(1));
+        let entry = load_entry(path.clone()).unwrap();
+        assert_eq!(entry.game, "Second");
+        assert_eq!(entry.category, "Any%");
+    }
+
+    #[test]
+    fn skips_missing_files() {
+        let dir = test_dir("missing");
+        assert!(load_entry(dir.join("Missing.lss").into()).is_none());
+    }
+
+    #[test]
+    fn scans_nested_folders() {
+        let dir = test_dir("scan");
+        fs::create_dir_all(dir.join("Game/Category")).unwrap();
+        write_splits(&dir.join("Top.lss"), "Top");
+        write_splits(&dir.join("Game/Category/Nested.lss"), "Nested");
+
+        let mut paths = BTreeSet::new();
+        scan(&dir, 0, &mut paths);
+        assert!(paths.contains(dir.join("Top.lss").as_path()));
+        assert!(paths.contains(dir.join("Game/Category/Nested.lss").as_path()));
+    }
+}
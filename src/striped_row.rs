@@ -0,0 +1,72 @@
+use druid::{
+    BoxConstraints, Color, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx,
+    PaintCtx, RenderContext, Size, UpdateCtx, Widget,
+};
+
+/// A row of a table that knows its position in the table.
+pub trait Striped: Data {
+    fn index(&self) -> usize;
+}
+
+/// Alternates the background of the rows of a table, so they are easier to
+/// tell apart.
+pub struct StripedRow<T, W> {
+    inner: W,
+    index: Box<dyn Fn(&T) -> usize>,
+}
+
+impl<T: Striped, W> StripedRow<T, W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            index: Box::new(T::index),
+        }
+    }
+}
+
+impl<T: Data, W> StripedRow<T, W> {
+    /// A row that always stays at the same position, like the rows of a form.
+    pub fn at(index: usize, inner: W) -> Self {
+        Self {
+            inner,
+            index: Box::new(move |_: &T| index),
+        }
+    }
+}
+
+impl<T: Data, W: Widget<T>> Widget<T> for StripedRow<T, W> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        self.inner.event(ctx, event, data, env)
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        self.inner.lifecycle(ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+        if !old_data.same(data) {
+            ctx.request_paint();
+        }
+        self.inner.update(ctx, old_data, data, env)
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        self.inner.layout(ctx, bc, data, env)
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
+        let rect = ctx.size().to_rect();
+        ctx.fill(rect, &stripe_color((self.index)(data)));
+        self.inner.paint(ctx, data, env)
+    }
+}
+
+/// The background of the row at the index, for rows that have other
+/// backgrounds as well, like the selected segments.
+pub fn stripe_color(index: usize) -> Color {
+    if index & 1 == 0 {
+        Color::grey8(0x12)
+    } else {
+        Color::grey8(0xb)
+    }
+}
@@ -3,36 +3,49 @@ use std::{
     io::{BufReader, Cursor, Seek, SeekFrom},
     path::{Path, PathBuf},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
+use anyhow::anyhow;
 use druid::{
     commands,
     menu::MenuEntry,
-    piet::{Device, ImageFormat, PietImage},
+    piet::{Device, ImageFormat, PietImage, Text, TextLayout, TextLayoutBuilder},
     theme,
     widget::{Controller, Flex},
-    AppDelegate, AppLauncher, BoxConstraints, Command, DelegateCtx, Env, Event, EventCtx,
-    FileDialogOptions, FileInfo, FileSpec, LayoutCtx, LifeCycle, LifeCycleCtx, LocalizedString,
-    Menu, MenuItem, MouseButton, Point, RenderContext, Selector, Size, UpdateCtx, Widget,
-    WidgetExt, WindowDesc, WindowId, WindowLevel,
+    AppDelegate, AppLauncher, Application, BoxConstraints, Color, Command, DelegateCtx, Env, Event,
+    EventCtx, FileDialogOptions, FileInfo, FileSpec, FontFamily, HotKey, LayoutCtx, LifeCycle,
+    LifeCycleCtx, LocalizedString, Menu, MenuItem, MouseButton, Point, Rect, RenderContext,
+    Selector, Size, SysMods, UpdateCtx, Widget, WidgetExt, WindowDesc, WindowId, WindowLevel,
 };
 use livesplit_core::{
     layout::{self, LayoutSettings},
     run::parser::{composite, TimerKind},
-    Layout, LayoutEditor, RunEditor, TimerPhase, TimingMethod,
+    Layout, LayoutEditor, Run, RunEditor, TimerPhase, TimingMethod,
 };
 use native_dialog::MessageType;
 use once_cell::sync::OnceCell;
 
 use crate::{
-    config::{or_show_error, show_error},
+    app_hotkeys::{self, Action},
+    config::{or_show_error, show_error, Settings},
     consts::{
         BACKGROUND, BUTTON_BORDER, BUTTON_BORDER_RADIUS, BUTTON_BOTTOM, BUTTON_TOP, MARGIN,
         PRIMARY_LIGHT, SELECTED_TEXT_BACKGROUND_COLOR, TEXTBOX_BACKGROUND,
     },
-    layout_editor, run_editor, settings_editor, software_renderer, LayoutEditorLens, MainState,
-    OpenWindow, RunEditorLens, SettingsEditorLens, FONT_FAMILIES, HOTKEY_SYSTEM,
+    export, history_merge, hotkey_button, layout_editor, log_viewer, notes,
+    pasted_files::{self, FileKind},
+    practice::Practice,
+    progression, run_editor,
+    safeguards::{self, Reset},
+    settings_editor, software_renderer, splits_comparison, splits_library,
+    timing::timing_method_name,
+    LayoutEditorLens, LogViewerLens, MainState, NotesLens, OpenWindow, ProgressionLens,
+    RunEditorLens, SettingsEditorLens, SplitsComparisonLens, SplitsLibraryLens, FONT_FAMILIES,
+    HOTKEY_SYSTEM,
 };
+#[cfg(feature = "auto-splitting")]
+use crate::{auto_splitter_inspector, AutoSplitterInspectorLens};
 
 struct WithMenu<T> {
     // device: Device,
@@ -41,6 +54,10 @@ struct WithMenu<T> {
     inner: T,
     intent: Intent,
     intent_path: Option<Arc<Path>>,
+    export_format: export::Format,
+    toast: Option<(String, Instant, Duration)>,
+    practice: Option<Practice>,
+    record_practice_golds: bool,
 }
 
 impl<T> WithMenu<T> {
@@ -63,6 +80,10 @@ impl<T> WithMenu<T> {
             inner,
             intent: Intent::NONE,
             intent_path: None,
+            export_format: export::Format::Csv,
+            toast: None,
+            practice: None,
+            record_practice_golds: false,
         }
     }
 }
@@ -99,6 +120,9 @@ impl Intent {
     }
 }
 
+const TOAST_DURATION: Duration = Duration::from_millis(1500);
+const WARNING_TOAST_DURATION: Duration = Duration::from_secs(6);
+
 const CONTEXT_MENU_EDIT_SPLITS: Selector = Selector::new("context-menu-edit-splits");
 const CONTEXT_MENU_SET_INTENT: Selector<Intent> = Selector::new("context-menu-set-intent");
 const CONTEXT_MENU_SET_INTENT_WITH_PATH: Selector<(Intent, Arc<Path>)> =
@@ -106,8 +130,18 @@ const CONTEXT_MENU_SET_INTENT_WITH_PATH: Selector<(Intent, Arc<Path>)> =
 const CONTEXT_MENU_OPEN_SPLITS: Selector<FileInfo> = Selector::new("context-menu-open-splits");
 const CONTEXT_MENU_SAVE_SPLITS_AS: Selector<FileInfo> =
     Selector::new("context-menu-save-splits-as");
+const CONTEXT_MENU_EXPORT_SPLITS: Selector<export::Format> =
+    Selector::new("context-menu-export-splits");
+const CONTEXT_MENU_EXPORT_SPLITS_TO: Selector<FileInfo> =
+    Selector::new("context-menu-export-splits-to");
+const CONTEXT_MENU_TOGGLE_PIN_SPLITS: Selector = Selector::new("context-menu-toggle-pin-splits");
+const CONTEXT_MENU_CLEAR_RECENT_SPLITS: Selector =
+    Selector::new("context-menu-clear-recent-splits");
+const CONTEXT_MENU_CLEAR_RECENT_LAYOUTS: Selector =
+    Selector::new("context-menu-clear-recent-layouts");
 const CONTEXT_MENU_EDIT_LAYOUT: Selector = Selector::new("context-menu-edit-layout");
 const CONTEXT_MENU_OPEN_LAYOUT: Selector<FileInfo> = Selector::new("context-menu-open-layout");
+const CONTEXT_MENU_TOGGLE_PIN_LAYOUT: Selector = Selector::new("context-menu-toggle-pin-layout");
 const CONTEXT_MENU_SAVE_LAYOUT_AS: Selector<FileInfo> =
     Selector::new("context-menu-save-layout-as");
 const CONTEXT_MENU_START_OR_SPLIT: Selector = Selector::new("context-menu-start-or-split");
@@ -115,15 +149,222 @@ const CONTEXT_MENU_UNDO_SPLIT: Selector = Selector::new("context-menu-undo-split
 const CONTEXT_MENU_SKIP_SPLIT: Selector = Selector::new("context-menu-skip-split");
 const CONTEXT_MENU_TOGGLE_PAUSE: Selector = Selector::new("context-menu-toggle-pause");
 const CONTEXT_MENU_UNDO_ALL_PAUSES: Selector = Selector::new("context-menu-undo-all-pauses");
+const CONTEXT_MENU_START_PRACTICE: Selector<usize> = Selector::new("context-menu-start-practice");
+const CONTEXT_MENU_STOP_PRACTICE: Selector = Selector::new("context-menu-stop-practice");
+const CONTEXT_MENU_TOGGLE_PRACTICE_GOLDS: Selector =
+    Selector::new("context-menu-toggle-practice-golds");
 const CONTEXT_MENU_SET_COMPARISON: Selector<String> = Selector::new("context-menu-set-comparison");
 const CONTEXT_MENU_SET_TIMING_METHOD: Selector<TimingMethod> =
     Selector::new("context-menu-set-timing-method");
 const CONTEXT_MENU_EDIT_SETTINGS: Selector = Selector::new("context-menu-edit-settings");
+const CONTEXT_MENU_OPEN_LOG_VIEWER: Selector = Selector::new("context-menu-open-log-viewer");
+const CONTEXT_MENU_OPEN_NOTES: Selector = Selector::new("context-menu-open-notes");
+const CONTEXT_MENU_OPEN_PROGRESSION: Selector = Selector::new("context-menu-open-progression");
+const CONTEXT_MENU_OPEN_SPLITS_LIBRARY: Selector =
+    Selector::new("context-menu-open-splits-library");
+const CONTEXT_MENU_OPEN_SPLITS_COMPARISON: Selector =
+    Selector::new("context-menu-open-splits-comparison");
+const CONTEXT_MENU_MERGE_HISTORY: Selector = Selector::new("context-menu-merge-history");
+const CONTEXT_MENU_MERGE_HISTORY_FROM: Selector<FileInfo> =
+    Selector::new("context-menu-merge-history-from");
+#[cfg(feature = "auto-splitting")]
+const CONTEXT_MENU_OPEN_AUTO_SPLITTER_INSPECTOR: Selector =
+    Selector::new("context-menu-open-auto-splitter-inspector");
+
+impl<T> WithMenu<T> {
+    /// Druid doesn't support dropping files onto a window, so files copied in
+    /// a file manager can be pasted onto the timer instead. They go through
+    /// the same intents as opening them through the menu, so unsaved changes
+    /// are still protected.
+    fn open_pasted_files(&mut self, ctx: &mut EventCtx, data: &MainState, text: &str) {
+        if data.run_editor.is_some()
+            || data.layout_editor.is_some()
+            || data.settings_editor.is_some()
+        {
+            return;
+        }
+
+        let path = match pasted_files::paths_from_text(text).into_iter().next() {
+            Some(path) => path,
+            None => return,
+        };
+
+        let intent = match pasted_files::detect_file_kind(&path) {
+            Ok(FileKind::Splits) => Intent::RESET
+                .with(Intent::MAYBE_SAVE_SPLITS)
+                .with(Intent::OPEN_SPLITS),
+            Ok(FileKind::Layout) => Intent::MAYBE_SAVE_LAYOUT.with(Intent::OPEN_LAYOUT),
+            Err(e) => return show_error(e),
+        };
+        ctx.submit_command(CONTEXT_MENU_SET_INTENT_WITH_PATH.with((intent, path.into())));
+    }
+
+    /// Applies the actions of the application hotkeys. Like the global
+    /// hotkeys, they are ignored while an editor is open.
+    fn handle_app_hotkeys(&mut self, data: &mut MainState) {
+        let actions = app_hotkeys::take_triggered();
+        if app_hotkeys::take_reset_ignored() {
+            self.show_reset_ignored();
+        }
+        self.show_hotkey_failures();
+        if data.run_editor.is_some()
+            || data.layout_editor.is_some()
+            || data.settings_editor.is_some()
+        {
+            return;
+        }
+
+        for action in actions {
+            let mut app_hotkeys = data.app_hotkeys.borrow_mut();
+            if !app_hotkeys.global_hotkeys_enabled && action != Action::ToggleGlobalHotkeys {
+                continue;
+            }
+
+            let message = match action {
+                Action::PreviousComparison | Action::NextComparison => {
+                    let mut timer = data.timer.write().unwrap();
+                    if action == Action::PreviousComparison {
+                        timer.switch_to_previous_comparison();
+                    } else {
+                        timer.switch_to_next_comparison();
+                    }
+                    let comparison = timer.current_comparison().to_owned();
+                    drop(timer);
+                    data.config.borrow_mut().set_comparison(comparison.clone());
+                    format!("Comparison: {comparison}")
+                }
+                Action::ToggleTimingMethod => {
+                    let mut timer = data.timer.write().unwrap();
+                    timer.toggle_timing_method();
+                    let timing_method = timer.current_timing_method();
+                    drop(timer);
+                    data.config.borrow_mut().set_timing_method(timing_method);
+                    format!("Timing Method: {}", timing_method_name(timing_method))
+                }
+                Action::PreviousNotes | Action::NextNotes => {
+                    if let Some(window) = &mut data.notes {
+                        window
+                            .state
+                            .scroll(if action == Action::NextNotes { 1 } else { -1 });
+                    }
+                    continue;
+                }
+                Action::Reset => {
+                    // Only resets that need to be confirmed end up here.
+                    drop(app_hotkeys);
+                    if safeguards::confirm_reset() {
+                        data.timer.write().unwrap().reset(true);
+                    }
+                    continue;
+                }
+                Action::ToggleGlobalHotkeys => {
+                    let enabled = !app_hotkeys.global_hotkeys_enabled;
+                    app_hotkeys.global_hotkeys_enabled = enabled;
+                    app_hotkeys.set_active(enabled);
+                    let mut hotkey_system = HOTKEY_SYSTEM.write().unwrap();
+                    let hotkey_system = hotkey_system.as_mut().unwrap();
+                    if enabled {
+                        hotkey_system.activate();
+                    } else {
+                        hotkey_system.deactivate();
+                    }
+                    format!("Global Hotkeys: {}", if enabled { "On" } else { "Off" })
+                }
+                _ => continue,
+            };
+            self.toast = Some((message, Instant::now(), TOAST_DURATION));
+        }
+    }
+
+    /// Applies the safeguards to a reset. Only the reset itself needs to be
+    /// pressed twice, other actions that reset the timer at most ask for a
+    /// confirmation.
+    fn allow_reset(&mut self, data: &MainState, implicit: bool) -> bool {
+        let reset = if implicit {
+            safeguards::check_implicit_reset(&data.timer.read().unwrap())
+        } else {
+            safeguards::check_reset(&data.timer.read().unwrap())
+        };
+        match reset {
+            Reset::Allowed => true,
+            Reset::NeedsConfirmation => safeguards::confirm_reset(),
+            Reset::Ignored => {
+                self.show_reset_ignored();
+                false
+            }
+        }
+    }
+
+    fn show_reset_ignored(&mut self) {
+        self.toast = Some((
+            String::from("Reset again to reset"),
+            Instant::now(),
+            TOAST_DURATION,
+        ));
+    }
+
+    /// Tells the user about the hotkeys that couldn't be registered globally.
+    /// The details are in the log.
+    fn show_hotkey_failures(&mut self) {
+        let failed = app_hotkeys::take_failures();
+        if failed.is_empty() {
+            return;
+        }
+        let hotkeys = failed
+            .iter()
+            .map(|hotkey| hotkey.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.toast = Some((
+            format!("Couldn't register globally: {hotkeys}"),
+            Instant::now(),
+            WARNING_TOAST_DURATION,
+        ));
+    }
+
+    fn stop_practice(&mut self, data: &MainState) {
+        if let Some(practice) = self.practice.take() {
+            practice.stop(&mut data.timer.write().unwrap(), self.record_practice_golds);
+        }
+    }
+
+    fn paint_toast(&mut self, ctx: &mut druid::PaintCtx) {
+        if matches!(&self.toast, Some((_, shown_at, duration)) if shown_at.elapsed() >= *duration) {
+            self.toast = None;
+        }
+        let message = match &self.toast {
+            Some((message, ..)) => message,
+            None => return,
+        };
+
+        let layout = match ctx
+            .text()
+            .new_text_layout(message.clone())
+            .font(FontFamily::SYSTEM_UI, 14.0)
+            .text_color(Color::WHITE)
+            .build()
+        {
+            Ok(layout) => layout,
+            Err(_) => return,
+        };
+
+        let size = ctx.size();
+        let text_size = layout.size();
+        let rect = Rect::from_center_size(
+            Point::new(size.width / 2.0, size.height - text_size.height - 10.0),
+            text_size + Size::new(16.0, 8.0),
+        )
+        .to_rounded_rect(4.0);
+        ctx.fill(rect, &Color::rgba8(0, 0, 0, 0xc0));
+        ctx.draw_text(&layout, rect.rect().center() - text_size.to_vec2() / 2.0);
+    }
+}
 
 impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
     fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut MainState, env: &Env) {
         match event {
             Event::AnimFrame(_) => {
+                self.handle_app_hotkeys(data);
                 ctx.request_anim_frame();
                 ctx.request_paint();
             }
@@ -134,6 +375,25 @@ impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
                     data.layout_data.borrow_mut().layout.scroll_up();
                 }
             }
+            Event::WindowConnected => {
+                // The timer needs the focus to receive pasted files.
+                ctx.request_focus();
+            }
+            // The paste event is only sent through the Edit menu, which the
+            // timer doesn't have, so the shortcut is handled directly.
+            Event::KeyDown(event) if HotKey::new(SysMods::Cmd, "v").matches(event) => {
+                if let Some(text) = Application::global().clipboard().get_string() {
+                    self.open_pasted_files(ctx, data, &text);
+                }
+                ctx.set_handled();
+            }
+            Event::KeyDown(event) if !event.repeat => {
+                if let hotkey_button::Hotkey(Some(hotkey)) = hotkey_button::Hotkey::new(event) {
+                    if data.app_hotkeys.borrow().handle_key(hotkey) {
+                        ctx.set_handled();
+                    }
+                }
+            }
             Event::MouseUp(event) => {
                 if event.button == MouseButton::Right
                     && data.run_editor.is_none()
@@ -150,31 +410,53 @@ impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
                     let current_timing_method = timer.current_timing_method();
                     let current_phase = timer.current_phase();
 
+                    let config = data.config.borrow();
+                    let open_splits = Intent::RESET
+                        .with(Intent::MAYBE_SAVE_SPLITS)
+                        .with(Intent::OPEN_SPLITS);
+                    let open_layout = Intent::MAYBE_SAVE_LAYOUT.with(Intent::OPEN_LAYOUT);
+
                     let mut open_recent = Menu::new("Open Recent");
-                    for (game, categories) in data.config.borrow().splits_history() {
+                    for path in config.pinned_splits() {
+                        open_recent = open_recent.entry(recent_file(path, open_splits));
+                    }
+                    if !config.pinned_splits().is_empty() {
+                        open_recent = open_recent.separator();
+                    }
+                    for (game, categories) in config.splits_history() {
                         let mut game = Menu::new(game.clone());
                         for (category, paths) in categories {
                             let mut category = Menu::new(category.clone());
                             for path in paths {
-                                let name = match path.file_name() {
-                                    Some(name) => name.to_string_lossy().into_owned(),
-                                    None => String::from("Untitled"),
-                                };
-                                category = category.entry(
-                                    MenuItem::new(name).command(
-                                        CONTEXT_MENU_SET_INTENT_WITH_PATH.with((
-                                            Intent::RESET
-                                                .with(Intent::MAYBE_SAVE_SPLITS)
-                                                .with(Intent::OPEN_SPLITS),
-                                            path.clone(),
-                                        )),
-                                    ),
-                                );
+                                category = category.entry(recent_file(path, open_splits));
                             }
                             game = game.entry(category);
                         }
                         open_recent = open_recent.entry(game);
                     }
+                    open_recent = open_recent.separator().entry(
+                        MenuItem::new("Clear Recent").command(CONTEXT_MENU_CLEAR_RECENT_SPLITS),
+                    );
+
+                    let mut open_recent_layout = Menu::new("Open Recent");
+                    for path in config.pinned_layouts() {
+                        open_recent_layout =
+                            open_recent_layout.entry(recent_file(path, open_layout));
+                    }
+                    if !config.pinned_layouts().is_empty() {
+                        open_recent_layout = open_recent_layout.separator();
+                    }
+                    for path in config.recent_layouts() {
+                        open_recent_layout =
+                            open_recent_layout.entry(recent_file(path, open_layout));
+                    }
+                    open_recent_layout = open_recent_layout.separator().entry(
+                        MenuItem::new("Clear Recent").command(CONTEXT_MENU_CLEAR_RECENT_LAYOUTS),
+                    );
+
+                    let splits_pinned = config.are_splits_pinned();
+                    let layout_pinned = config.is_layout_pinned();
+                    drop(config);
 
                     for comparison in timer.run().comparisons() {
                         compare_against = compare_against.entry(
@@ -241,13 +523,38 @@ impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
                             .into()
                     };
 
-                    ctx.show_context_menu::<MainState>(
+                    let practice_menu = if self.practice.is_some() {
+                        Menu::new("Practice").entry(
+                            MenuItem::new("Stop Practicing").command(CONTEXT_MENU_STOP_PRACTICE),
+                        )
+                    } else {
+                        let mut start_from = Menu::new("Start From");
+                        for (index, segment) in timer.run().segments().iter().enumerate() {
+                            start_from = start_from.entry(
+                                MenuItem::new(segment.name())
+                                    .enabled(current_phase == TimerPhase::NotRunning)
+                                    .command(CONTEXT_MENU_START_PRACTICE.with(index)),
+                            );
+                        }
+                        Menu::new("Practice").entry(start_from)
+                    }
+                    .separator()
+                    .entry(
+                        MenuItem::new("Record Golds")
+                            .selected(self.record_practice_golds)
+                            .command(CONTEXT_MENU_TOGGLE_PRACTICE_GOLDS),
+                    );
+
+                    let menu =
                         Menu::new("LiveSplit")
                             .entry(
                                 Menu::new("Splits")
                                     .entry(
                                         MenuItem::new("Edit...")
-                                            .enabled(current_phase == TimerPhase::NotRunning)
+                                            .enabled(
+                                                current_phase == TimerPhase::NotRunning
+                                                    && self.practice.is_none(),
+                                            )
                                             .command(CONTEXT_MENU_EDIT_SPLITS),
                                     )
                                     .separator()
@@ -270,6 +577,16 @@ impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
                                         ),
                                     )
                                     .entry(open_recent)
+                                    .entry(
+                                        MenuItem::new("Library...")
+                                            .command(CONTEXT_MENU_OPEN_SPLITS_LIBRARY),
+                                    )
+                                    .entry(
+                                        MenuItem::new("Pinned")
+                                            .selected(splits_pinned == Some(true))
+                                            .enabled(splits_pinned.is_some())
+                                            .command(CONTEXT_MENU_TOGGLE_PIN_SPLITS),
+                                    )
                                     .separator()
                                     .entry(
                                         MenuItem::new("Save").command(
@@ -282,6 +599,39 @@ impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
                                             CONTEXT_MENU_SET_INTENT
                                                 .with(Intent::RESET.with(Intent::SAVE_SPLITS_AS)),
                                         ),
+                                    )
+                                    .entry(
+                                        Menu::new("Export As")
+                                            .entry(MenuItem::new("CSV...").command(
+                                                CONTEXT_MENU_EXPORT_SPLITS
+                                                    .with(export::Format::Csv),
+                                            ))
+                                            .entry(
+                                                MenuItem::new("Splits.io JSON...").command(
+                                                    CONTEXT_MENU_EXPORT_SPLITS
+                                                        .with(export::Format::SplitsIo),
+                                                ),
+                                            )
+                                            .entry(
+                                                MenuItem::new("Route Sheet...").command(
+                                                    CONTEXT_MENU_EXPORT_SPLITS
+                                                        .with(export::Format::RouteSheet),
+                                                ),
+                                            ),
+                                    )
+                                    .separator()
+                                    .entry(
+                                        MenuItem::new("Compare With...")
+                                            .command(CONTEXT_MENU_OPEN_SPLITS_COMPARISON),
+                                    )
+                                    .entry(
+                                        MenuItem::new("Merge History...")
+                                            .enabled(
+                                                current_phase == TimerPhase::NotRunning
+                                                    && self.practice.is_none()
+                                                    && data.run_editor.is_none(),
+                                            )
+                                            .command(CONTEXT_MENU_MERGE_HISTORY),
                                     ),
                             )
                             .entry(
@@ -300,6 +650,14 @@ impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
                                             Intent::MAYBE_SAVE_LAYOUT.with(Intent::OPEN_LAYOUT),
                                         ),
                                     ))
+                                    .entry(open_recent_layout)
+                                    .entry(
+                                        MenuItem::new("Pinned")
+                                            .selected(layout_pinned == Some(true))
+                                            .enabled(layout_pinned.is_some())
+                                            .command(CONTEXT_MENU_TOGGLE_PIN_LAYOUT),
+                                    )
+                                    .separator()
                                     .entry(
                                         MenuItem::new("Save").command(
                                             CONTEXT_MENU_SET_INTENT.with(Intent::SAVE_LAYOUT),
@@ -311,52 +669,101 @@ impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
                             )
                             .separator()
                             .entry(control_menu)
+                            .entry(practice_menu)
                             .entry(compare_against)
                             .separator()
                             .entry(MenuItem::new("Settings").command(CONTEXT_MENU_EDIT_SETTINGS))
-                            .separator()
+                            .entry(MenuItem::new("Notes").command(CONTEXT_MENU_OPEN_NOTES))
                             .entry(
-                                MenuItem::new("Exit").command(
-                                    CONTEXT_MENU_SET_INTENT.with(
-                                        Intent::RESET
-                                            .with(Intent::MAYBE_SAVE_SPLITS)
-                                            .with(Intent::MAYBE_SAVE_LAYOUT)
-                                            .with(Intent::EXIT),
-                                    ),
+                                MenuItem::new("Progression").command(CONTEXT_MENU_OPEN_PROGRESSION),
+                            )
+                            .entry(MenuItem::new("Log").command(CONTEXT_MENU_OPEN_LOG_VIEWER));
+
+                    #[cfg(feature = "auto-splitting")]
+                    let menu = menu.entry(
+                        MenuItem::new("Auto Splitter Inspector")
+                            .command(CONTEXT_MENU_OPEN_AUTO_SPLITTER_INSPECTOR),
+                    );
+
+                    ctx.show_context_menu::<MainState>(
+                        menu.separator().entry(
+                            MenuItem::new("Exit").command(
+                                CONTEXT_MENU_SET_INTENT.with(
+                                    Intent::RESET
+                                        .with(Intent::MAYBE_SAVE_SPLITS)
+                                        .with(Intent::MAYBE_SAVE_LAYOUT)
+                                        .with(Intent::EXIT),
                                 ),
                             ),
+                        ),
                         event.pos,
                     );
                 }
             }
             Event::Command(command) => {
+                #[cfg(feature = "auto-splitting")]
+                if command.is(CONTEXT_MENU_OPEN_AUTO_SPLITTER_INSPECTOR) {
+                    open_auto_splitter_inspector(ctx, data);
+                }
+
                 if command.is(CONTEXT_MENU_EDIT_SPLITS) {
-                    HOTKEY_SYSTEM
-                        .write()
-                        .unwrap()
-                        .as_mut()
-                        .unwrap()
-                        .deactivate();
                     let run = data.timer.read().unwrap().run().clone();
-                    let editor = RunEditor::new(run).unwrap();
-                    let window = WindowDesc::new(run_editor::root_widget().lens(RunEditorLens))
-                        .title("Splits Editor")
-                        .with_min_size((690.0, 495.0))
-                        .window_size((690.0, 495.0))
-                        // TODO: WindowLevel::Modal(ctx.window().clone())
-                        .set_level(WindowLevel::AppWindow);
-                    let window_id = window.id;
-                    ctx.new_window(window);
-                    data.run_editor = Some(OpenWindow {
-                        id: window_id,
-                        state: run_editor::State::new(editor, data.config.clone()),
-                    });
+                    open_run_editor(ctx, data, run);
+                } else if command.is(CONTEXT_MENU_MERGE_HISTORY) {
+                    ctx.submit_command(
+                        commands::SHOW_OPEN_PANEL.with(
+                            FileDialogOptions::new()
+                                .title("Merge History From Splits")
+                                .allowed_types(vec![
+                                    FileSpec {
+                                        name: "LiveSplit Splits",
+                                        extensions: &["lss"],
+                                    },
+                                    FileSpec {
+                                        name: "All Files",
+                                        extensions: &["*.*"],
+                                    },
+                                ])
+                                .accept_command(CONTEXT_MENU_MERGE_HISTORY_FROM),
+                        ),
+                    );
+                } else if let Some(file_info) = command.get(CONTEXT_MENU_MERGE_HISTORY_FROM) {
+                    let result = {
+                        let timer = data.timer.read().unwrap();
+                        history_merge::merge_file(
+                            timer.run(),
+                            file_info.path(),
+                            timer.current_timing_method(),
+                        )
+                    };
+                    match result {
+                        Ok((run, report)) => {
+                            // The merged splits are only applied once they are
+                            // accepted in the splits editor.
+                            let wants_to_review = native_dialog::MessageDialog::new()
+                                .set_title("Merge History?")
+                                .set_text(&format!("{report}\n\nDo you want to review the merged splits in the splits editor?"))
+                                .set_type(MessageType::Info)
+                                .show_confirm()
+                                .unwrap_or(false);
+                            if wants_to_review {
+                                open_run_editor(ctx, data, run);
+                            }
+                        }
+                        Err(e) => show_error(e),
+                    }
                 } else if let Some(file_info) = command.get(CONTEXT_MENU_OPEN_SPLITS) {
                     let result = data.config.borrow_mut().open_splits(
                         &mut data.timer.write().unwrap(),
                         &mut data.layout_data.borrow_mut(),
                         file_info.path().to_path_buf(),
                     );
+                    #[cfg(feature = "auto-splitting")]
+                    if result.is_ok() {
+                        data.config
+                            .borrow_mut()
+                            .maybe_load_auto_splitter(&data.auto_splitter);
+                    }
                     or_show_error(result);
                 } else if let Some(file_info) = command.get(CONTEXT_MENU_SAVE_SPLITS_AS) {
                     let result = data.config.borrow_mut().save_splits_as(
@@ -364,13 +771,35 @@ impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
                         file_info.path().to_path_buf(),
                     );
                     or_show_error(result);
+                } else if let Some(format) = command.get(CONTEXT_MENU_EXPORT_SPLITS) {
+                    self.export_format = *format;
+                    ctx.submit_command(
+                        commands::SHOW_SAVE_PANEL.with(
+                            FileDialogOptions::new()
+                                .title(format.title())
+                                .allowed_types(vec![format.file_spec()])
+                                .default_type(format.file_spec())
+                                .accept_command(CONTEXT_MENU_EXPORT_SPLITS_TO),
+                        ),
+                    );
+                } else if let Some(file_info) = command.get(CONTEXT_MENU_EXPORT_SPLITS_TO) {
+                    let timer = data.timer.read().unwrap();
+                    or_show_error(export::export(
+                        self.export_format,
+                        timer.run(),
+                        timer.current_timing_method(),
+                        file_info.path(),
+                    ));
+                } else if command.is(CONTEXT_MENU_TOGGLE_PIN_SPLITS) {
+                    data.config.borrow_mut().toggle_pin_splits();
+                } else if command.is(CONTEXT_MENU_TOGGLE_PIN_LAYOUT) {
+                    data.config.borrow_mut().toggle_pin_layout();
+                } else if command.is(CONTEXT_MENU_CLEAR_RECENT_SPLITS) {
+                    data.config.borrow_mut().clear_recent_splits();
+                } else if command.is(CONTEXT_MENU_CLEAR_RECENT_LAYOUTS) {
+                    data.config.borrow_mut().clear_recent_layouts();
                 } else if command.is(CONTEXT_MENU_EDIT_LAYOUT) {
-                    HOTKEY_SYSTEM
-                        .write()
-                        .unwrap()
-                        .as_mut()
-                        .unwrap()
-                        .deactivate();
+                    deactivate_hotkeys(data);
                     let layout = data.layout_data.borrow().layout.clone();
                     let editor = LayoutEditor::new(layout).unwrap();
                     let window =
@@ -405,7 +834,7 @@ impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
                     }
                     or_show_error(result);
                 } else if command.is(CONTEXT_MENU_START_OR_SPLIT) {
-                    data.timer.write().unwrap().split_or_start();
+                    safeguards::split_or_start(&mut data.timer.write().unwrap());
                 } else if command.is(CONTEXT_MENU_UNDO_SPLIT) {
                     data.timer.write().unwrap().undo_split();
                 } else if command.is(CONTEXT_MENU_SKIP_SPLIT) {
@@ -429,12 +858,7 @@ impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
                         .set_current_timing_method(*timing_method);
                     data.config.borrow_mut().set_timing_method(*timing_method);
                 } else if command.is(CONTEXT_MENU_EDIT_SETTINGS) {
-                    HOTKEY_SYSTEM
-                        .write()
-                        .unwrap()
-                        .as_mut()
-                        .unwrap()
-                        .deactivate();
+                    deactivate_hotkeys(data);
                     let window =
                         WindowDesc::new(settings_editor::root_widget().lens(SettingsEditorLens))
                             .title("Settings")
@@ -444,27 +868,97 @@ impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
                             .set_level(WindowLevel::AppWindow);
                     let window_id = window.id;
                     ctx.new_window(window);
-                    let config = HOTKEY_SYSTEM.read().unwrap().as_ref().unwrap().config();
                     data.settings_editor = Some(OpenWindow {
                         id: window_id,
-                        state: settings_editor::State::new(config),
+                        state: settings_editor::State::new(
+                            &data.config.borrow(),
+                            &data.timer.read().unwrap(),
+                        ),
                     });
+                } else if command.is(CONTEXT_MENU_OPEN_LOG_VIEWER) {
+                    open_log_viewer(ctx, data);
+                } else if command.is(CONTEXT_MENU_OPEN_NOTES) {
+                    open_notes(ctx, data);
+                } else if command.is(CONTEXT_MENU_OPEN_PROGRESSION) {
+                    open_progression(ctx, data);
+                } else if command.is(CONTEXT_MENU_OPEN_SPLITS_LIBRARY) {
+                    open_splits_library(ctx, data);
+                } else if command.is(CONTEXT_MENU_OPEN_SPLITS_COMPARISON) {
+                    open_splits_comparison(ctx, data);
+                } else if let Some((name, other)) =
+                    command.get(splits_comparison::IMPORT_COMPARISON)
+                {
+                    // The splits editor would overwrite the comparison, and
+                    // the practice copy of the splits is thrown away once the
+                    // practice is over.
+                    if data.run_editor.is_some() {
+                        show_error(anyhow!(
+                            "The comparison can't be imported while the splits are being edited."
+                        ));
+                    } else if self.practice.is_some() {
+                        show_error(anyhow!(
+                            "The comparison can't be imported while practicing."
+                        ));
+                    } else {
+                        or_show_error(splits_comparison::import_comparison(
+                            &mut data.timer.write().unwrap(),
+                            name,
+                            other,
+                        ));
+                    }
+                } else if let Some(path) = command.get(splits_library::OPEN_SPLITS) {
+                    // The splits can't be replaced while they are being edited.
+                    if data.run_editor.is_none() {
+                        self.intent = Intent::RESET
+                            .with(Intent::MAYBE_SAVE_SPLITS)
+                            .with(Intent::OPEN_SPLITS);
+                        self.intent_path = Some(path.clone());
+                    }
+                } else if let Some(&start) = command.get(CONTEXT_MENU_START_PRACTICE) {
+                    self.practice = Practice::start(&mut data.timer.write().unwrap(), start);
+                } else if command.is(CONTEXT_MENU_STOP_PRACTICE) {
+                    self.stop_practice(data);
+                } else if command.is(CONTEXT_MENU_TOGGLE_PRACTICE_GOLDS) {
+                    self.record_practice_golds = !self.record_practice_golds;
                 } else if let Some(intent) = command.get(CONTEXT_MENU_SET_INTENT) {
                     self.intent = *intent;
                 } else if let Some((intent, path)) = command.get(CONTEXT_MENU_SET_INTENT_WITH_PATH)
                 {
-                    self.intent = *intent;
-                    self.intent_path = Some(path.clone());
+                    if path.exists() {
+                        self.intent = *intent;
+                        self.intent_path = Some(path.clone());
+                    } else {
+                        maybe_forget_missing_file(data, path);
+                    }
+                }
+
+                // Everything that resets a running timer goes through the
+                // safeguards. Only Control → Reset is a reset on its own, the
+                // others reset while opening other splits or exiting.
+                if self.intent.contains(Intent::RESET)
+                    && !self.allow_reset(data, self.intent != Intent::RESET)
+                {
+                    self.intent = Intent::NONE;
+                }
+
+                // Everything but resetting the timer is about the actual splits,
+                // so the practice is over at that point.
+                if self.intent.without(Intent::RESET) != Intent::NONE {
+                    self.stop_practice(data);
                 }
 
                 while self.intent != Intent::NONE {
                     if self.intent.contains(Intent::RESET) {
                         self.intent = self.intent.without(Intent::RESET);
-                        let wants_to_save_times = if data
-                            .timer
-                            .read()
-                            .unwrap()
-                            .current_attempt_has_new_best_times()
+                        // The practice copy of the splits always keeps its
+                        // best times, whether they are taken over is up to
+                        // the practice.
+                        let wants_to_save_times = if self.practice.is_none()
+                            && data
+                                .timer
+                                .read()
+                                .unwrap()
+                                .current_attempt_has_new_best_times()
                         {
                             let result = native_dialog::MessageDialog::new()
                                 .set_title("Update Times?")
@@ -505,7 +999,27 @@ impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
 
                     if self.intent.contains(Intent::SAVE_SPLITS) {
                         self.intent = self.intent.without(Intent::SAVE_SPLITS);
-                        if data.config.borrow().can_directly_save_splits() {
+                        let warning = data
+                            .config
+                            .borrow()
+                            .unsupported_splits_data(data.timer.read().unwrap().run());
+                        let keep_format = data.config.borrow().can_directly_save_splits()
+                            && match warning {
+                                Some(warning) => {
+                                    let confirmed = native_dialog::MessageDialog::new()
+                                        .set_title("Save Splits?")
+                                        .set_text(&format!("{warning} Do you still want to save them in that format? Choose No to save them as LiveSplit splits instead."))
+                                        .set_type(MessageType::Warning)
+                                        .show_confirm()
+                                        .unwrap_or(false);
+                                    if confirmed {
+                                        data.config.borrow_mut().confirm_lossy_save();
+                                    }
+                                    confirmed
+                                }
+                                None => true,
+                            };
+                        if keep_format {
                             let result = data
                                 .config
                                 .borrow_mut()
@@ -527,6 +1041,10 @@ impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
                         data.config
                             .borrow_mut()
                             .new_splits(&mut data.timer.write().unwrap());
+                        #[cfg(feature = "auto-splitting")]
+                        data.config
+                            .borrow_mut()
+                            .maybe_load_auto_splitter(&data.auto_splitter);
                     }
 
                     if self.intent.contains(Intent::OPEN_SPLITS) {
@@ -606,22 +1124,29 @@ impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
 
                     if self.intent.contains(Intent::OPEN_LAYOUT) {
                         self.intent = self.intent.without(Intent::OPEN_LAYOUT);
-                        let open_dialog = commands::SHOW_OPEN_PANEL.with(
-                            FileDialogOptions::new()
-                                .title("Open Layout")
-                                .allowed_types(vec![
-                                    FileSpec {
-                                        name: "LiveSplit Layouts",
-                                        extensions: &["lsl", "ls1l"],
-                                    },
-                                    FileSpec {
-                                        name: "All Files",
-                                        extensions: &["*.*"],
-                                    },
-                                ])
-                                .accept_command(CONTEXT_MENU_OPEN_LAYOUT),
-                        );
-                        ctx.submit_command(open_dialog);
+                        let command = if let Some(path) = self.intent_path.take() {
+                            CONTEXT_MENU_OPEN_LAYOUT.with(FileInfo {
+                                path: path.to_path_buf(),
+                                format: None,
+                            })
+                        } else {
+                            commands::SHOW_OPEN_PANEL.with(
+                                FileDialogOptions::new()
+                                    .title("Open Layout")
+                                    .allowed_types(vec![
+                                        FileSpec {
+                                            name: "LiveSplit Layouts",
+                                            extensions: &["lsl", "ls1l"],
+                                        },
+                                        FileSpec {
+                                            name: "All Files",
+                                            extensions: &["*.*"],
+                                        },
+                                    ])
+                                    .accept_command(CONTEXT_MENU_OPEN_LAYOUT),
+                            )
+                        };
+                        ctx.submit_command(command);
                         break;
                     }
 
@@ -645,6 +1170,7 @@ impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
         _env: &Env,
     ) {
         if let LifeCycle::WidgetAdded = event {
+            ctx.register_for_focus();
             ctx.request_anim_frame();
             ctx.request_paint();
         }
@@ -713,9 +1239,183 @@ impl<T: Widget<MainState>> Widget<MainState> for WithMenu<T> {
             ctx.window()
                 .set_size(Size::new(new_width as _, new_height as _));
         }
+
+        self.paint_toast(ctx);
     }
 }
 
+#[cfg(feature = "auto-splitting")]
+fn open_auto_splitter_inspector(ctx: &mut EventCtx, data: &mut MainState) {
+    if let Some(window) = &data.auto_splitter_inspector {
+        ctx.submit_command(commands::SHOW_WINDOW.to(window.id));
+        return;
+    }
+    let window =
+        WindowDesc::new(auto_splitter_inspector::root_widget().lens(AutoSplitterInspectorLens))
+            .title("Auto Splitter Inspector")
+            .with_min_size((400.0, 400.0))
+            .window_size((500.0, 600.0))
+            .set_level(WindowLevel::AppWindow);
+    let window_id = window.id;
+    ctx.new_window(window);
+    data.auto_splitter_inspector = Some(OpenWindow {
+        id: window_id,
+        state: auto_splitter_inspector::State::new(data.timer.clone()),
+    });
+}
+
+/// Offers to remove a recent or pinned file that no longer exists. It stays if
+/// the user declines, as it may just be on a drive that isn't connected.
+fn maybe_forget_missing_file(data: &mut MainState, path: &Path) {
+    let result = native_dialog::MessageDialog::new()
+        .set_title("Missing File")
+        .set_text(&format!(
+            "\"{}\" no longer exists. Do you want to remove it from the recent files?",
+            path.display(),
+        ))
+        .set_type(MessageType::Warning)
+        .show_confirm();
+
+    if let Ok(true) = result {
+        data.config.borrow_mut().forget_missing_file(path);
+    }
+}
+
+fn recent_file(path: &Arc<Path>, intent: Intent) -> MenuItem<MainState> {
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => String::from("Untitled"),
+    };
+    MenuItem::new(name).command(CONTEXT_MENU_SET_INTENT_WITH_PATH.with((intent, path.clone())))
+}
+
+fn open_log_viewer(ctx: &mut EventCtx, data: &mut MainState) {
+    if let Some(window) = &data.log_viewer {
+        ctx.submit_command(commands::SHOW_WINDOW.to(window.id));
+        return;
+    }
+    let window = WindowDesc::new(log_viewer::root_widget().lens(LogViewerLens))
+        .title("Log")
+        .with_min_size((500.0, 300.0))
+        .window_size((800.0, 500.0))
+        .set_level(WindowLevel::AppWindow);
+    let window_id = window.id;
+    ctx.new_window(window);
+    data.log_viewer = Some(OpenWindow {
+        id: window_id,
+        state: log_viewer::State::new(),
+    });
+}
+
+fn open_notes(ctx: &mut EventCtx, data: &mut MainState) {
+    if let Some(window) = &data.notes {
+        ctx.submit_command(commands::SHOW_WINDOW.to(window.id));
+        return;
+    }
+    let window = WindowDesc::new(notes::root_widget().lens(NotesLens))
+        .title("Notes")
+        .with_min_size((250.0, 150.0))
+        .window_size((400.0, 300.0))
+        .set_level(WindowLevel::AppWindow);
+    let window_id = window.id;
+    ctx.new_window(window);
+    data.notes = Some(OpenWindow {
+        id: window_id,
+        state: notes::State::new(data.timer.clone(), data.config.clone()),
+    });
+}
+
+fn open_progression(ctx: &mut EventCtx, data: &mut MainState) {
+    if let Some(window) = &data.progression {
+        ctx.submit_command(commands::SHOW_WINDOW.to(window.id));
+        return;
+    }
+    let window = WindowDesc::new(progression::root_widget().lens(ProgressionLens))
+        .title("Progression")
+        .with_min_size((500.0, 300.0))
+        .window_size((800.0, 500.0))
+        .set_level(WindowLevel::AppWindow);
+    let window_id = window.id;
+    ctx.new_window(window);
+    data.progression = Some(OpenWindow {
+        id: window_id,
+        state: progression::State::new(data.timer.clone()),
+    });
+}
+
+fn open_run_editor(ctx: &mut EventCtx, data: &mut MainState, run: Run) {
+    deactivate_hotkeys(data);
+    let editor = RunEditor::new(run).unwrap();
+    let window = WindowDesc::new(run_editor::root_widget().lens(RunEditorLens))
+        .title("Splits Editor")
+        .with_min_size((690.0, 620.0))
+        .window_size((690.0, 620.0))
+        // TODO: WindowLevel::Modal(ctx.window().clone())
+        .set_level(WindowLevel::AppWindow);
+    let window_id = window.id;
+    ctx.new_window(window);
+    data.run_editor = Some(OpenWindow {
+        id: window_id,
+        state: run_editor::State::new(editor, data.config.clone()),
+    });
+}
+
+fn open_splits_comparison(ctx: &mut EventCtx, data: &mut MainState) {
+    if let Some(window) = &data.splits_comparison {
+        ctx.submit_command(commands::SHOW_WINDOW.to(window.id));
+        return;
+    }
+    let window = WindowDesc::new(splits_comparison::root_widget().lens(SplitsComparisonLens))
+        .title("Compare Splits")
+        .with_min_size((800.0, 300.0))
+        .window_size((950.0, 600.0))
+        .set_level(WindowLevel::AppWindow);
+    let window_id = window.id;
+    ctx.new_window(window);
+    data.splits_comparison = Some(OpenWindow {
+        id: window_id,
+        state: splits_comparison::State::new(data.timer.clone()),
+    });
+}
+
+fn open_splits_library(ctx: &mut EventCtx, data: &mut MainState) {
+    if let Some(window) = &data.splits_library {
+        ctx.submit_command(commands::SHOW_WINDOW.to(window.id));
+        return;
+    }
+    let window = WindowDesc::new(splits_library::root_widget().lens(SplitsLibraryLens))
+        .title("Splits Library")
+        .with_min_size((700.0, 300.0))
+        .window_size((900.0, 500.0))
+        .set_level(WindowLevel::AppWindow);
+    let window_id = window.id;
+    ctx.new_window(window);
+    data.splits_library = Some(OpenWindow {
+        id: window_id,
+        state: splits_library::State::new(data.config.clone()),
+    });
+}
+
+/// Reactivates the global hotkeys once an editor is closed, unless they were
+/// toggled off.
+fn activate_hotkeys(data: &MainState) {
+    let app_hotkeys = data.app_hotkeys.borrow();
+    if app_hotkeys.global_hotkeys_enabled {
+        HOTKEY_SYSTEM.write().unwrap().as_mut().unwrap().activate();
+        app_hotkeys.set_active(true);
+    }
+}
+
+fn deactivate_hotkeys(data: &MainState) {
+    HOTKEY_SYSTEM
+        .write()
+        .unwrap()
+        .as_mut()
+        .unwrap()
+        .deactivate();
+    data.app_hotkeys.borrow().set_active(false);
+}
+
 fn build_save_splits_as() -> druid::Command {
     commands::SHOW_SAVE_PANEL.with(
         FileDialogOptions::new()
@@ -805,15 +1505,30 @@ impl AppDelegate<MainState> for WindowManagement {
             if id == window.id {
                 if window.state.closed_with_ok {
                     let run = window.state.editor.borrow_mut().take().unwrap().close();
+                    or_show_error(
+                        data.config
+                            .borrow_mut()
+                            .set_notes(window.state.notes().clone()),
+                    );
                     data.timer
                         .write()
                         .unwrap()
                         .set_run(run)
                         .map_err(drop)
                         .unwrap();
+
+                    #[cfg(feature = "auto-splitting")]
+                    {
+                        let mut config = data.config.borrow_mut();
+                        let auto_splitter = window.state.auto_splitter.trim();
+                        config.set_auto_splitter_for_current_splits(
+                            (!auto_splitter.is_empty()).then(|| PathBuf::from(auto_splitter)),
+                        );
+                        config.maybe_load_auto_splitter(&data.auto_splitter);
+                    }
                 }
                 data.run_editor = None;
-                HOTKEY_SYSTEM.write().unwrap().as_mut().unwrap().activate();
+                activate_hotkeys(data);
                 return;
             }
         }
@@ -827,7 +1542,50 @@ impl AppDelegate<MainState> for WindowManagement {
                     layout_data.is_modified = true;
                 }
                 data.layout_editor = None;
-                HOTKEY_SYSTEM.write().unwrap().as_mut().unwrap().activate();
+                activate_hotkeys(data);
+                return;
+            }
+        }
+
+        if let Some(window) = &data.log_viewer {
+            if id == window.id {
+                data.log_viewer = None;
+                return;
+            }
+        }
+
+        if let Some(window) = &data.splits_library {
+            if id == window.id {
+                data.splits_library = None;
+                return;
+            }
+        }
+
+        if let Some(window) = &data.splits_comparison {
+            if id == window.id {
+                data.splits_comparison = None;
+                return;
+            }
+        }
+
+        if let Some(window) = &data.notes {
+            if id == window.id {
+                data.notes = None;
+                return;
+            }
+        }
+
+        if let Some(window) = &data.progression {
+            if id == window.id {
+                data.progression = None;
+                return;
+            }
+        }
+
+        #[cfg(feature = "auto-splitting")]
+        if let Some(window) = &data.auto_splitter_inspector {
+            if id == window.id {
+                data.auto_splitter_inspector = None;
                 return;
             }
         }
@@ -835,17 +1593,64 @@ impl AppDelegate<MainState> for WindowManagement {
         if let Some(window) = &data.settings_editor {
             if id == window.id {
                 if window.state.closed_with_ok {
-                    let hotkey_config = window.state.editor.borrow_mut().take().unwrap();
-                    HOTKEY_SYSTEM
-                        .write()
-                        .unwrap()
-                        .as_mut()
-                        .unwrap()
-                        .set_config(hotkey_config);
-                    data.config.borrow_mut().set_hotkeys(hotkey_config);
+                    let state = &window.state;
+                    let hotkey_config = state.hotkey_config();
+                    let app_hotkey_primaries = state.app_hotkey_primaries();
+                    let alternate_hotkeys = state.alternate_hotkeys();
+                    let handled_by_system = app_hotkeys::configure_system(
+                        HOTKEY_SYSTEM.write().unwrap().as_mut().unwrap(),
+                        hotkey_config,
+                        state.hotkey_mode(),
+                    );
+                    let failed_hotkeys = data.app_hotkeys.borrow_mut().configure(
+                        &hotkey_config,
+                        &app_hotkey_primaries,
+                        &alternate_hotkeys,
+                        state.hotkey_mode(),
+                        handled_by_system,
+                    );
+                    app_hotkeys::warn_about_failures(&failed_hotkeys);
+
+                    let mut timer = data.timer.write().unwrap();
+                    timer.set_current_timing_method(state.timing_method());
+                    // The comparison was picked from the timer's list, so it
+                    // always exists.
+                    let _ = timer.set_current_comparison(state.comparison());
+                    drop(timer);
+
+                    let mut config = data.config.borrow_mut();
+                    config.apply_settings(Settings {
+                        hotkeys: hotkey_config,
+                        app_hotkey_primaries,
+                        alternate_hotkeys,
+                        hotkey_mode: state.hotkey_mode(),
+                        timing_method: state.timing_method(),
+                        comparison: state.comparison().to_owned(),
+                        window_size: state.window_size(),
+                        log: state.log(),
+                        safeguards: state.safeguards(),
+                        #[cfg(feature = "auto-splitting")]
+                        auto_splitter: state.auto_splitter(),
+                    });
+                    safeguards::configure(state.safeguards());
+
+                    #[cfg(feature = "auto-splitting")]
+                    config.maybe_load_auto_splitter(&data.auto_splitter);
+
+                    let layout = state.layout();
+                    if layout.as_deref() != config.layout_path() {
+                        ctx.submit_command(match layout {
+                            Some(path) => CONTEXT_MENU_SET_INTENT_WITH_PATH.with((
+                                Intent::MAYBE_SAVE_LAYOUT.with(Intent::OPEN_LAYOUT),
+                                path.into(),
+                            )),
+                            None => CONTEXT_MENU_SET_INTENT
+                                .with(Intent::MAYBE_SAVE_LAYOUT.with(Intent::NEW_LAYOUT)),
+                        });
+                    }
                 }
                 data.settings_editor = None;
-                HOTKEY_SYSTEM.write().unwrap().as_mut().unwrap().activate();
+                activate_hotkeys(data);
                 return;
             }
         }
@@ -0,0 +1,69 @@
+use livesplit_core::{TimeSpan, TimingMethod};
+
+/// Both timing methods, in the order they are listed in.
+pub const TIMING_METHODS: [TimingMethod; 2] = [TimingMethod::RealTime, TimingMethod::GameTime];
+
+/// The names of the timing methods, in the same order, for picking one of them
+/// in a combo box.
+pub const TIMING_METHOD_NAMES: &[&str] = &["Real Time", "Game Time"];
+
+pub fn timing_method_index(timing_method: TimingMethod) -> usize {
+    match timing_method {
+        TimingMethod::RealTime => 0,
+        TimingMethod::GameTime => 1,
+    }
+}
+
+pub fn timing_method_from_index(index: usize) -> TimingMethod {
+    TIMING_METHODS
+        .get(index)
+        .copied()
+        .unwrap_or(TimingMethod::RealTime)
+}
+
+pub fn timing_method_name(timing_method: TimingMethod) -> &'static str {
+    TIMING_METHOD_NAMES[timing_method_index(timing_method)]
+}
+
+pub fn other_timing_method(timing_method: TimingMethod) -> TimingMethod {
+    match timing_method {
+        TimingMethod::RealTime => TimingMethod::GameTime,
+        TimingMethod::GameTime => TimingMethod::RealTime,
+    }
+}
+
+/// The shortest of the times. The first one wins if there are several.
+pub fn fastest(times: impl IntoIterator<Item = TimeSpan>) -> Option<TimeSpan> {
+    times
+        .into_iter()
+        .fold(None, |fastest: Option<TimeSpan>, time| match fastest {
+            Some(fastest) if fastest <= time => Some(fastest),
+            _ => Some(time),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indices_match_the_names() {
+        for (index, timing_method) in TIMING_METHODS.into_iter().enumerate() {
+            assert_eq!(timing_method_index(timing_method), index);
+            assert_eq!(timing_method_from_index(index), timing_method);
+            assert_ne!(other_timing_method(timing_method), timing_method);
+        }
+        assert_eq!(timing_method_name(TimingMethod::GameTime), "Game Time");
+        assert_eq!(TIMING_METHOD_NAMES.len(), TIMING_METHODS.len());
+    }
+
+    #[test]
+    fn fastest_time() {
+        let seconds = |seconds: &[f64]| seconds.iter().copied().map(TimeSpan::from_seconds);
+        assert_eq!(
+            fastest(seconds(&[3.0, 1.0, 2.0])),
+            Some(TimeSpan::from_seconds(1.0))
+        );
+        assert_eq!(fastest(seconds(&[])), None);
+    }
+}